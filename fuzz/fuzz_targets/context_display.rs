@@ -0,0 +1,112 @@
+//! Render contexts with arbitrary text, highlights, and display settings to find panics in the
+//! rendering code. Run with `cargo +nightly fuzz run context_display` from the root of the repository.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use context_error::*;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    text: String,
+    source: Option<String>,
+    line_index: Option<u64>,
+    first_line_offset: u8,
+    wide_text: Option<(u8, String)>,
+    highlights: Vec<(u8, u16, HighlightLength, Option<String>, bool, Style)>,
+    width: u8,
+    unicode: bool,
+    line_numbers: bool,
+    max_comment_width: u8,
+    long_comment_footnotes: bool,
+    force_ltr: bool,
+    wrap_lines: bool,
+    caret_only: bool,
+    symbol_set: Symbols,
+}
+
+#[derive(Arbitrary, Debug)]
+enum Symbols {
+    Unicode,
+    WindowsSafe,
+    Ascii,
+}
+
+#[derive(Arbitrary, Debug)]
+enum Style {
+    Underline,
+    Box,
+    Pointer,
+    Background,
+}
+
+#[derive(Arbitrary, Debug)]
+enum HighlightLength {
+    Length(u16),
+    ToEnd,
+}
+
+fuzz_target!(|input: Input| {
+    DisplayConfig::default()
+        .colour(false)
+        .width(usize::from(input.width))
+        .unicode(input.unicode)
+        .line_numbers(input.line_numbers)
+        .max_comment_width(usize::from(input.max_comment_width))
+        .long_comment_footnotes(input.long_comment_footnotes)
+        .force_ltr(input.force_ltr)
+        .wrap_lines(input.wrap_lines)
+        .caret_only(input.caret_only)
+        .symbol_set(match input.symbol_set {
+            Symbols::Unicode => SymbolSet::Unicode,
+            Symbols::WindowsSafe => SymbolSet::WindowsSafe,
+            Symbols::Ascii => SymbolSet::Ascii,
+        })
+        .set_thread_local();
+
+    // Arbitrary strings rarely contain wide characters, so mix some in to reach the widened paths
+    let mut text = input.text;
+    if let Some((position, wide)) = input.wide_text {
+        let position = text
+            .char_indices()
+            .nth(usize::from(position))
+            .map_or(text.len(), |(index, _)| index);
+        text.insert_str(position, &format!("日本語{wide}ｗｉｄｅ"));
+    }
+
+    let mut context = Context::default().lines(u64::from(input.first_line_offset), text.as_str());
+    if let Some(source) = input.source {
+        context = context.source(source);
+    }
+    if let Some(line_index) = input.line_index {
+        context = context.line_index(line_index);
+    }
+    for (line, offset, length, comment, primary, style) in input.highlights {
+        let length = match length {
+            HighlightLength::Length(n) => usize::from(n),
+            HighlightLength::ToEnd => usize::MAX,
+        };
+        let mut highlight = Highlight::from((usize::from(line), usize::from(offset), length));
+        if let Some(comment) = comment {
+            highlight = highlight.comment(comment);
+        }
+        let style = match style {
+            Style::Underline => HighlightStyle::Underline,
+            Style::Box => HighlightStyle::Box,
+            Style::Pointer => HighlightStyle::Pointer,
+            Style::Background => HighlightStyle::Background,
+        };
+        context = context.add_highlight(highlight.primary(primary).style(style));
+    }
+
+    let _ = context.to_string();
+    let _ = format!("{context:#}");
+    let _ = context.location_string();
+    let error: BoxedError<'_, BasicKind> =
+        BoxedError::new(BasicKind::Error, "Fuzz", "", context.clone());
+    let _ = error.to_string();
+    let _ = FullErrorContent::to_html(&error, true);
+    let _ = FullErrorContent::to_html(&error, false);
+    let _ = FullErrorContent::to_compact(&error);
+    let _ = render_context_group(&[context.clone(), context], Some("note"));
+});
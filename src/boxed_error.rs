@@ -1,7 +1,10 @@
 use core::fmt;
 use std::{borrow::Cow, error};
 
-use crate::{Context, CreateError, CustomError, ErrorKind, FullErrorContent, StaticErrorContent};
+use crate::{
+    BasicKind, Context, CreateError, CustomError, ErrorKind, ErrorSummary, FullErrorContent,
+    StaticErrorContent,
+};
 
 /// An error. Stored as a pointer to a structure on the heap to prevent large sizes which could be
 /// detrimental to performance for the happy path.
@@ -11,6 +14,9 @@ pub struct BoxedError<'text, Kind> {
     pub(crate) content: Box<CustomError<'text, Kind>>,
 }
 
+/// A result with a [`BoxedError`] as error, the kind defaults to [`BasicKind`]
+pub type CResult<'text, T, Kind = BasicKind> = Result<T, BoxedError<'text, Kind>>;
+
 impl<'text, Kind: 'text> StaticErrorContent<'text> for BoxedError<'text, Kind> {
     /// Gives the short description or title for this error
     fn get_short_description(&self) -> Cow<'text, str> {
@@ -31,6 +37,40 @@ impl<'text, Kind: 'text> StaticErrorContent<'text> for BoxedError<'text, Kind> {
     fn get_version(&self) -> Cow<'text, str> {
         self.content.version.clone()
     }
+
+    /// Borrows the short description
+    fn short_description_ref<'a>(&'a self) -> Cow<'a, str>
+    where
+        'text: 'a,
+    {
+        Cow::Borrowed(&self.content.short_description)
+    }
+
+    /// Borrows the long description
+    fn long_description_ref<'a>(&'a self) -> Cow<'a, str>
+    where
+        'text: 'a,
+    {
+        Cow::Borrowed(&self.content.long_description)
+    }
+
+    /// Borrows the version
+    fn version_ref<'a>(&'a self) -> Cow<'a, str>
+    where
+        'text: 'a,
+    {
+        Cow::Borrowed(&self.content.version)
+    }
+
+    /// The notes
+    fn get_notes<'a>(&'a self) -> Cow<'a, [Cow<'text, str>]> {
+        Cow::Borrowed(self.content.notes.as_slice())
+    }
+
+    /// The expected and found version
+    fn get_version_mismatch(&self) -> Option<crate::VersionMismatch> {
+        self.content.get_version_mismatch()
+    }
 }
 
 impl<'text, Kind: 'text + Clone + PartialEq + ErrorKind> FullErrorContent<'text, Kind>
@@ -70,14 +110,19 @@ impl<'text, Kind: ErrorKind + 'text + Clone> CreateError<'text, Kind> for BoxedE
         self
     }
 
-    /// Extend the suggestions with the given suggestions, does not remove any previously added suggestions
+    /// Extend the suggestions with the given suggestions, does not remove any previously added suggestions.
+    /// Suggestions that are already present are skipped.
     fn suggestions(
         mut self,
         suggestions: impl IntoIterator<Item = impl Into<Cow<'text, str>>>,
     ) -> Self {
-        self.content
-            .suggestions
-            .extend(suggestions.into_iter().map(|s| s.into()));
+        self.content = Box::new(self.content.suggestions(suggestions));
+        self
+    }
+
+    /// Add the given notes, will append to the current list.
+    fn notes(mut self, notes: impl IntoIterator<Item = impl Into<Cow<'text, str>>>) -> Self {
+        self.content.notes.extend(notes.into_iter().map(Into::into));
         self
     }
 
@@ -87,6 +132,12 @@ impl<'text, Kind: ErrorKind + 'text + Clone> CreateError<'text, Kind> for BoxedE
         self
     }
 
+    /// Set the expected version
+    fn expected_version(mut self, expected: impl Into<Cow<'text, str>>) -> Self {
+        self.content.expected_version = Some(expected.into());
+        self
+    }
+
     /// Update with a new context
     fn replace_context(mut self, context: Context<'text>) -> Self {
         self.content.contexts = vec![context];
@@ -128,7 +179,7 @@ impl<'text, Kind: ErrorKind + 'text + Clone> CreateError<'text, Kind> for BoxedE
     }
 
     /// Set the context line index
-    fn overwrite_line_index(mut self, line_index: u32) -> Self {
+    fn overwrite_line_index(mut self, line_index: u64) -> Self {
         self.content.contexts = self
             .content
             .contexts
@@ -137,9 +188,42 @@ impl<'text, Kind: ErrorKind + 'text + Clone> CreateError<'text, Kind> for BoxedE
             .collect();
         self
     }
+
+    /// Transform the short description
+    fn map_short_description(mut self, f: impl FnOnce(Cow<'text, str>) -> Cow<'text, str>) -> Self {
+        self.content.short_description = f(std::mem::take(&mut self.content.short_description));
+        self
+    }
+
+    /// Transform the long description
+    fn map_long_description(mut self, f: impl FnOnce(Cow<'text, str>) -> Cow<'text, str>) -> Self {
+        self.content.long_description = f(std::mem::take(&mut self.content.long_description));
+        self
+    }
+
+    /// Transform all contexts
+    fn map_contexts(mut self, f: impl FnMut(Context<'text>) -> Context<'text>) -> Self {
+        self.content.contexts = std::mem::take(&mut self.content.contexts)
+            .into_iter()
+            .map(f)
+            .collect();
+        self
+    }
 }
 
 impl<'text, Kind: ErrorKind> BoxedError<'text, Kind> {
+    /// Get a copy of this error with the text of all contexts redacted, including the contexts
+    /// of the underlying errors, see [`Context::redact`]. The descriptions and suggestions are
+    /// kept as is.
+    pub fn redact(&self) -> BoxedError<'static, Kind>
+    where
+        Kind: Clone,
+    {
+        BoxedError {
+            content: Box::new(self.content.redact()),
+        }
+    }
+
     /// (Possibly) clone the text to get a static valid error
     pub fn to_owned(self) -> BoxedError<'static, Kind> {
         BoxedError {
@@ -148,19 +232,41 @@ impl<'text, Kind: ErrorKind> BoxedError<'text, Kind> {
     }
 }
 
+impl<Kind: ErrorKind> ErrorSummary for BoxedError<'_, Kind> {
+    fn kind_descriptor(&self) -> &'static str {
+        self.content.kind_descriptor()
+    }
+
+    fn title(&self) -> &str {
+        self.content.title()
+    }
+
+    fn description(&self) -> &str {
+        self.content.description()
+    }
+
+    fn contexts(&self) -> &[Context<'_>] {
+        self.content.contexts()
+    }
+}
+
 impl<Kind: ErrorKind + Clone> fmt::Debug for BoxedError<'_, Kind> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.display(f, None, true)
+        crate::fmt_error(self, f, None)
     }
 }
 
 impl<Kind: ErrorKind + Clone> fmt::Display for BoxedError<'_, Kind> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.display(f, None, true)
+        crate::fmt_error(self, f, None)
     }
 }
 
-impl<Kind: ErrorKind + Clone> error::Error for BoxedError<'_, Kind> {}
+impl<Kind: ErrorKind + Clone> error::Error for BoxedError<'_, Kind> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.content.source()
+    }
+}
 
 impl<'text, Kind: ErrorKind> From<CustomError<'text, Kind>> for BoxedError<'text, Kind> {
     fn from(value: CustomError<'text, Kind>) -> Self {
@@ -169,3 +275,35 @@ impl<'text, Kind: ErrorKind> From<CustomError<'text, Kind>> for BoxedError<'text
         }
     }
 }
+
+/// Create an error of kind [`BasicKind::Error`] with the message as short description, see
+/// [`CreateError::message`]
+impl<'text> From<&'text str> for BoxedError<'text, BasicKind> {
+    fn from(value: &'text str) -> Self {
+        Self::message(BasicKind::Error, value)
+    }
+}
+
+/// Create an error of kind [`BasicKind::Error`] with the message as short description, see
+/// [`CreateError::message`]
+impl From<String> for BoxedError<'_, BasicKind> {
+    fn from(value: String) -> Self {
+        Self::message(BasicKind::Error, value)
+    }
+}
+
+/// Create an error of kind [`BasicKind::Error`] with the IO error as short description, see
+/// [`CreateError::from_display`]. This allows `?` to be used on IO results in functions returning
+/// errors of this type.
+/// ```
+/// use context_error::*;
+/// fn read(path: &str) -> CResult<'static, String> {
+///     Ok(std::fs::read_to_string(path)?)
+/// }
+/// assert!(read("missing/file.txt").is_err());
+/// ```
+impl From<std::io::Error> for BoxedError<'_, BasicKind> {
+    fn from(value: std::io::Error) -> Self {
+        Self::from_display(BasicKind::Error, &value)
+    }
+}
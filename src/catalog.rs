@@ -0,0 +1,186 @@
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Context, CreateError, ErrorKind};
+
+/// A catalog of error definitions keyed by their code, so that the messages can be maintained
+/// outside of the Rust source. The catalog can be loaded from TOML (`toml` feature) or JSON
+/// (`json` feature), for example from a file embedded with [`include_str!`].
+/// ```
+/// # #[cfg(feature = "json")] {
+/// use context_error::*;
+/// let catalog: Catalog<BasicKind> = Catalog::from_json(r#"{
+///     "E001": {
+///         "kind": "error",
+///         "short_description": "Invalid number",
+///         "long_description": "This column is not a number",
+///         "suggestions": ["Use only digits"],
+///         "doc_url": "https://example.com/E001"
+///     }
+/// }"#).unwrap();
+/// let error: BoxedError<BasicKind> = catalog
+///     .create("E001", Context::default().lines(0, "null,80o0,YES").add_highlight((0, 5..9)))
+///     .unwrap();
+/// assert_eq!(error.get_short_description(), "Invalid number");
+/// assert_eq!(error.get_long_description(), "This column is not a number\nSee https://example.com/E001");
+/// # }
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Catalog<Kind> {
+    entries: HashMap<String, CatalogEntry<Kind>>,
+}
+
+/// A single error definition in a [`Catalog`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CatalogEntry<Kind> {
+    /// The kind of the error
+    #[serde(alias = "severity")]
+    pub kind: Kind,
+    /// A short description of the error, used as title line
+    pub short_description: String,
+    /// A longer description of the error
+    #[serde(default)]
+    pub long_description: String,
+    /// Suggestions to fix the error
+    #[serde(default)]
+    pub suggestions: Vec<String>,
+    /// A link to the documentation of this error, shown after the long description
+    #[serde(default)]
+    pub doc_url: Option<String>,
+}
+
+/// An error while loading a [`Catalog`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CatalogError(String);
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid error catalog: {}", self.0)
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+impl<Kind> Default for Catalog<Kind> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<Kind> Catalog<Kind>
+where
+    Kind: ErrorKind + Clone,
+{
+    /// Load a catalog from a TOML document with a table per error code
+    /// # Errors
+    /// If the document is not valid TOML or does not describe a catalog.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self, CatalogError>
+    where
+        Kind: for<'de> Deserialize<'de>,
+    {
+        toml::from_str(text).map_err(|e| CatalogError(e.to_string()))
+    }
+
+    /// Load a catalog from a JSON document with an object per error code
+    /// # Errors
+    /// If the document is not valid JSON or does not describe a catalog.
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self, CatalogError>
+    where
+        Kind: for<'de> Deserialize<'de>,
+    {
+        serde_json::from_str(text).map_err(|e| CatalogError(e.to_string()))
+    }
+
+    /// Add an entry, replacing any previous entry with the same code
+    #[must_use]
+    pub fn entry(mut self, code: impl Into<String>, entry: CatalogEntry<Kind>) -> Self {
+        self.entries.insert(code.into(), entry);
+        self
+    }
+
+    /// Get the entry for the given code
+    pub fn get(&self, code: &str) -> Option<&CatalogEntry<Kind>> {
+        self.entries.get(code)
+    }
+
+    /// All codes in this catalog, in arbitrary order
+    pub fn codes(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Create the error with the given code and context, returns `None` if the code is not in
+    /// this catalog. The documentation link is added to the long description.
+    pub fn create<'text, E: CreateError<'text, Kind>>(
+        &self,
+        code: &str,
+        context: Context<'text>,
+    ) -> Option<E> {
+        let entry = self.entries.get(code)?;
+        let long_description: Cow<'text, str> = match &entry.doc_url {
+            Some(url) if entry.long_description.is_empty() => format!("See {url}").into(),
+            Some(url) => format!("{}\nSee {url}", entry.long_description).into(),
+            None => entry.long_description.clone().into(),
+        };
+        Some(
+            E::new(
+                entry.kind.clone(),
+                entry.short_description.clone(),
+                long_description,
+                context,
+            )
+            .suggestions(entry.suggestions.iter().cloned()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicKind, BoxedError, StaticErrorContent};
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn toml() {
+        use crate::FullErrorContent;
+        let catalog: Catalog<BasicKind> = Catalog::from_toml(
+            r#"
+            [W002]
+            severity = "warning"
+            short_description = "Unused column"
+            suggestions = ["Remove the column"]
+            "#,
+        )
+        .unwrap();
+        let error: BoxedError<BasicKind> = catalog.create("W002", Context::default()).unwrap();
+        assert_eq!(error.get_kind(), BasicKind::Warning);
+        assert_eq!(error.get_long_description(), "");
+        assert_eq!(error.get_suggestions().as_ref(), ["Remove the column"]);
+        assert!(catalog
+            .create::<BoxedError<BasicKind>>("E001", Context::default())
+            .is_none());
+        assert!(Catalog::<BasicKind>::from_toml("[W002]\nkind = \"fatal\"").is_err());
+    }
+
+    #[test]
+    fn builder() {
+        let catalog = Catalog::default().entry(
+            "E001",
+            CatalogEntry {
+                kind: BasicKind::Error,
+                short_description: "Invalid number".to_string(),
+                long_description: String::new(),
+                suggestions: Vec::new(),
+                doc_url: Some("https://example.com/E001".to_string()),
+            },
+        );
+        assert_eq!(catalog.codes().collect::<Vec<_>>(), ["E001"]);
+        let error: BoxedError<BasicKind> = catalog.create("E001", Context::default()).unwrap();
+        assert_eq!(error.get_long_description(), "See https://example.com/E001");
+    }
+}
@@ -0,0 +1,321 @@
+use std::ops::Range;
+
+use codespan_reporting::{
+    diagnostic::{Diagnostic, Label, LabelStyle},
+    files::{Error, Files},
+};
+
+use crate::{
+    Context, CreateError, ErrorKind, FullErrorContent, Highlight, HighlightStyle,
+    StaticErrorContent,
+};
+
+/// Convert an error into a [`codespan_reporting`] diagnostic, for example to temporarily use the
+/// codespan renderer while migrating. The highlights of all contexts are converted into labels
+/// (the primary highlight of the first context being primary), `file_id` is used to look up the source of a context in the
+/// files database. Contexts without source, or with a source that cannot be found, and contexts
+/// without highlights, line number, or byte range are skipped. The long description, notes, suggestions,
+/// version, and the titles of the underlying errors are added as notes.
+/// ```
+/// use codespan_reporting::{diagnostic::Severity, files::SimpleFiles};
+/// use context_error::*;
+/// let mut files = SimpleFiles::new();
+/// let id = files.add("data.csv", "a,b\nnull,80o0,YES\n");
+/// let error = BoxedError::new(
+///     BasicKind::Error,
+///     "Invalid number",
+///     "This column is not a number",
+///     Context::default().source("data.csv").line_index(1).lines(0, "null,80o0,YES").add_highlight((0, 5..9)),
+/// );
+/// let diagnostic = to_codespan_diagnostic(&error, None, &files, |name| (name == "data.csv").then_some(id));
+/// assert_eq!(diagnostic.severity, Severity::Error);
+/// assert_eq!(diagnostic.labels[0].range, 9..13);
+/// ```
+pub fn to_codespan_diagnostic<'files, 'text, Kind, E, F>(
+    error: &E,
+    settings: Option<Kind::Settings>,
+    files: &'files F,
+    file_id: impl Fn(&str) -> Option<F::FileId>,
+) -> Diagnostic<F::FileId>
+where
+    Kind: ErrorKind,
+    E: FullErrorContent<'text, Kind>,
+    F: Files<'files>,
+{
+    let kind = error.get_kind();
+    let mut diagnostic = if settings.map_or(true, |settings| kind.is_error(settings)) {
+        Diagnostic::error()
+    } else {
+        Diagnostic::warning()
+    }
+    .with_message(error.get_short_description());
+
+    for context in error.get_contexts().iter() {
+        let Some(id) = context.get_source().and_then(&file_id) else {
+            continue;
+        };
+        for (range, comment, primary) in context_byte_ranges(context, files, id) {
+            let label = if primary
+                && !diagnostic
+                    .labels
+                    .iter()
+                    .any(|l| l.style == LabelStyle::Primary)
+            {
+                Label::primary(id, range)
+            } else {
+                Label::secondary(id, range)
+            };
+            diagnostic.labels.push(match comment {
+                Some(comment) => label.with_message(comment),
+                None => label,
+            });
+        }
+    }
+
+    if !error.long_description_ref().is_empty() {
+        diagnostic
+            .notes
+            .push(error.get_long_description().to_string());
+    }
+    for note in error.get_notes().iter() {
+        diagnostic.notes.push(format!("note: {note}"));
+    }
+    match error.get_suggestions().as_ref() {
+        [] => (),
+        [suggestion] => diagnostic
+            .notes
+            .push(format!("Did you mean: {suggestion}?")),
+        suggestions => diagnostic
+            .notes
+            .push(format!("Did you mean any of: {}?", suggestions.join(", "))),
+    }
+    if !error.version_ref().is_empty() {
+        diagnostic
+            .notes
+            .push(format!("Version: {}", error.get_version()));
+    }
+    for underlying in error.get_underlying_errors().iter() {
+        diagnostic.notes.push(format!(
+            "Underlying {}: {}",
+            underlying.get_kind().descriptor(),
+            underlying.get_short_description()
+        ));
+    }
+    diagnostic
+}
+
+/// Get the byte ranges in the file for the highlights of the given context, with their comment
+/// and if it is the primary highlight. If there are no highlights the byte range or otherwise
+/// the first line of the context is used.
+fn context_byte_ranges<'files, 'text, F: Files<'files>>(
+    context: &Context<'text>,
+    files: &'files F,
+    id: F::FileId,
+) -> Vec<(Range<usize>, Option<String>, bool)> {
+    let Some(line_index) = context.get_line_index() else {
+        return context
+            .get_byte_range()
+            .map(|range| vec![(range, None, true)])
+            .unwrap_or_default();
+    };
+    let Ok(source) = files.source(id) else {
+        return Vec::new();
+    };
+    let source = source.as_ref();
+    if context.get_highlights().is_empty() {
+        return context
+            .get_byte_range()
+            .or_else(|| files.line_range(id, line_index as usize).ok())
+            .map(|range| vec![(range, None, true)])
+            .unwrap_or_default();
+    }
+    let primary = context.primary_highlight();
+    context
+        .get_highlights()
+        .iter()
+        .filter_map(|highlight| {
+            let line = files
+                .line_range(id, line_index as usize + highlight.line)
+                .ok()?;
+            let text = source.get(line.clone())?.trim_end_matches(['\r', '\n']);
+            let offset = highlight.offset
+                + if highlight.line == 0 {
+                    context.get_line_offset() as usize
+                } else {
+                    0
+                };
+            let byte_offset = |chars: usize| {
+                text.char_indices()
+                    .nth(chars)
+                    .map_or(text.len(), |(index, _)| index)
+            };
+            let start = byte_offset(offset);
+            let end = byte_offset(offset.saturating_add(highlight.length));
+            Some((
+                line.start + start..line.start + end,
+                highlight.comment.as_ref().map(ToString::to_string),
+                primary.map_or(false, |p| std::ptr::eq(p, highlight)),
+            ))
+        })
+        .collect()
+}
+
+/// Convert a [`codespan_reporting`] diagnostic into an error of the given kind. Every label is
+/// converted into a context showing the lines it spans, with the source set to the name of the
+/// file. The message is used as short description and the notes as long description.
+/// # Errors
+/// If any file or location referenced by the labels cannot be found in the files database.
+/// ```
+/// use codespan_reporting::{diagnostic::{Diagnostic, Label}, files::SimpleFiles};
+/// use context_error::*;
+/// let mut files = SimpleFiles::new();
+/// let id = files.add("data.csv", "a,b\nnull,80o0,YES\n");
+/// let diagnostic = Diagnostic::error()
+///     .with_message("Invalid number")
+///     .with_label(Label::primary(id, 9..13).with_message("not a number"));
+/// let error: BoxedError<BasicKind> = from_codespan_diagnostic(&diagnostic, BasicKind::Error, &files).unwrap();
+/// assert_eq!(error.get_contexts()[0].get_line_index(), Some(1));
+/// assert_eq!(error.get_contexts()[0].location_string(), "data.csv:2:6-9");
+/// ```
+pub fn from_codespan_diagnostic<'files, Kind, E, F>(
+    diagnostic: &Diagnostic<F::FileId>,
+    kind: Kind,
+    files: &'files F,
+) -> Result<E, Error>
+where
+    Kind: ErrorKind,
+    E: CreateError<'static, Kind>,
+    F: Files<'files>,
+{
+    let contexts = diagnostic
+        .labels
+        .iter()
+        .map(|label| label_to_context(label, files))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(E::small(
+        kind,
+        diagnostic.message.clone(),
+        diagnostic.notes.join("\n"),
+    )
+    .add_contexts(contexts))
+}
+
+/// Convert a single label into a context
+fn label_to_context<'files, F: Files<'files>>(
+    label: &Label<F::FileId>,
+    files: &'files F,
+) -> Result<Context<'static>, Error> {
+    let source = files.source(label.file_id)?;
+    let source = source.as_ref();
+    let first = files.line_index(label.file_id, label.range.start)?;
+    let last = files.line_index(
+        label.file_id,
+        label.range.end.saturating_sub(1).max(label.range.start),
+    )?;
+    let start = files.line_range(label.file_id, first)?.start;
+    let end = files.line_range(label.file_id, last)?.end;
+    let lines = source
+        .get(start..end)
+        .ok_or(Error::IndexTooLarge {
+            given: end,
+            max: source.len(),
+        })?
+        .trim_end_matches(['\r', '\n']);
+
+    let mut highlights = Vec::new();
+    let mut line_start = start;
+    for (index, line) in lines.split('\n').enumerate() {
+        let line = line.trim_end_matches('\r');
+        let line_end = line_start + line.len();
+        let from = label.range.start.clamp(line_start, line_end);
+        let to = label.range.end.clamp(line_start, line_end);
+        let offset = source[line_start..from].chars().count();
+        let length = source[from..to].chars().count();
+        highlights.push(Highlight {
+            line: index,
+            offset,
+            length,
+            comment: (index == 0 && !label.message.is_empty())
+                .then(|| label.message.clone().into()),
+            primary: index == 0 && label.style == LabelStyle::Primary,
+            link: None,
+            style: HighlightStyle::Underline,
+        });
+        line_start = files
+            .line_range(label.file_id, first + index + 1)
+            .map_or(end, |r| r.start);
+    }
+
+    Ok(Context::default()
+        .source(files.name(label.file_id)?.to_string())
+        .line_index(first as u64)
+        .lines(0, lines.to_string())
+        .add_highlights(highlights)
+        .byte_range(label.range.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use codespan_reporting::files::SimpleFiles;
+
+    use super::*;
+    use crate::{BasicKind, BoxedError};
+
+    #[test]
+    fn round_trip() {
+        let mut files = SimpleFiles::new();
+        let id = files.add("data.csv", "a,b\nnull,80o0,YES\n7oo1,ok\n");
+        let error = BoxedError::new(
+            BasicKind::Error,
+            "Invalid number",
+            "This column is not a number",
+            Context::default()
+                .source("data.csv")
+                .line_index(1)
+                .lines(0, "null,80o0,YES\n7oo1,ok")
+                .add_highlight((0, 5..9, "here"))
+                .add_highlight((1, 0..4)),
+        )
+        .suggestions(["8000"]);
+        let diagnostic = to_codespan_diagnostic(&error, None, &files, |_| Some(id));
+        assert_eq!(diagnostic.message, "Invalid number");
+        assert_eq!(diagnostic.labels.len(), 2);
+        assert_eq!(diagnostic.labels[0].range, 9..13);
+        assert_eq!(diagnostic.labels[0].message, "here");
+        assert_eq!(diagnostic.labels[1].range, 18..22);
+        assert_eq!(
+            diagnostic.notes,
+            ["This column is not a number", "Did you mean: 8000?"]
+        );
+
+        let back: BoxedError<BasicKind> =
+            from_codespan_diagnostic(&diagnostic, BasicKind::Error, &files).unwrap();
+        assert_eq!(back.get_short_description(), "Invalid number");
+        assert_eq!(back.get_contexts().len(), 2);
+        assert_eq!(back.get_contexts()[0].get_lines(), "null,80o0,YES");
+        assert_eq!(
+            back.get_contexts()[0].get_highlights(),
+            [Highlight::from((0, 5, 4, "here")).primary(true)]
+        );
+        assert_eq!(back.get_contexts()[1].location_string(), "data.csv:3:1-4");
+    }
+
+    #[test]
+    fn multiline_label() {
+        let mut files = SimpleFiles::new();
+        let id = files.add("data", "first\nsecond\nthird");
+        let diagnostic: Diagnostic<usize> =
+            Diagnostic::warning().with_label(Label::primary(id, 2..9));
+        let error: BoxedError<BasicKind> =
+            from_codespan_diagnostic(&diagnostic, BasicKind::Warning, &files).unwrap();
+        let context = &error.get_contexts()[0];
+        assert_eq!(context.get_lines(), "first\nsecond");
+        assert_eq!(
+            context.get_highlights(),
+            [
+                Highlight::from((0, 2, 3)).primary(true),
+                Highlight::from((1, 0, 3))
+            ]
+        );
+    }
+}
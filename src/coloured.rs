@@ -1,131 +1,209 @@
-#[cfg(feature = "colored")]
-use colored;
-
-pub(crate) trait Coloured {
-    type Output;
-    fn blue(self) -> Self::Output;
-    fn yellow(self) -> Self::Output;
-    fn red(self) -> Self::Output;
-    fn green(self) -> Self::Output;
-    fn dimmed(self) -> Self::Output;
+use std::{cell::RefCell, fmt, ops::Range};
+
+/// The styles used by the renderer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Style {
+    Blue,
+    Yellow,
+    Red,
+    Green,
+    Magenta,
+    Cyan,
+    Dimmed,
+    Bold,
+    /// Reversed colours, used as background for highlighted text
+    Background,
 }
 
-#[cfg(not(feature = "colored"))]
-impl Coloured for String {
-    type Output = String;
-    fn blue(self) -> Self::Output {
-        self
-    }
-    fn yellow(self) -> Self::Output {
-        self
-    }
-    fn red(self) -> Self::Output {
-        self
-    }
-    fn green(self) -> Self::Output {
-        self
-    }
-    fn dimmed(self) -> Self::Output {
-        self
+impl Style {
+    /// The name of this style, used as class name in SVG output
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Blue => "blue",
+            Self::Yellow => "yellow",
+            Self::Red => "red",
+            Self::Green => "green",
+            Self::Magenta => "magenta",
+            Self::Cyan => "cyan",
+            Self::Dimmed => "dimmed",
+            Self::Bold => "bold",
+            Self::Background => "background",
+        }
     }
 }
 
-#[cfg(feature = "colored")]
-impl Coloured for String {
-    type Output = colored::ColoredString;
-    fn blue(self) -> Self::Output {
-        colored::Colorize::blue(self.as_str())
-    }
-    fn yellow(self) -> Self::Output {
-        colored::Colorize::yellow(self.as_str())
-    }
-    fn red(self) -> Self::Output {
-        colored::Colorize::red(self.as_str())
-    }
-    fn green(self) -> Self::Output {
-        colored::Colorize::green(self.as_str())
-    }
-    fn dimmed(self) -> Self::Output {
-        colored::Colorize::dimmed(self.as_str())
+impl From<crate::Color> for Style {
+    fn from(value: crate::Color) -> Self {
+        match value {
+            crate::Color::Red => Self::Red,
+            crate::Color::Green => Self::Green,
+            crate::Color::Yellow => Self::Yellow,
+            crate::Color::Blue => Self::Blue,
+            crate::Color::Magenta => Self::Magenta,
+            crate::Color::Cyan => Self::Cyan,
+        }
     }
 }
 
-#[cfg(not(feature = "colored"))]
-impl<'a> Coloured for &'a str {
-    type Output = &'a str;
-    fn blue(self) -> Self::Output {
-        self
-    }
-    fn yellow(self) -> Self::Output {
-        self
-    }
-    fn red(self) -> Self::Output {
-        self
-    }
-    fn green(self) -> Self::Output {
-        self
-    }
-    fn dimmed(self) -> Self::Output {
-        self
-    }
+/// The styles recorded while rendering, see [`record_styles`]
+struct Recording {
+    /// The number of bytes of the rendered text written so far
+    written: usize,
+    /// The byte ranges of the rendered text that are styled
+    runs: Vec<(Style, Range<usize>)>,
 }
 
-#[cfg(feature = "colored")]
-impl Coloured for &str {
-    type Output = colored::ColoredString;
-    fn blue(self) -> Self::Output {
-        colored::Colorize::blue(self)
-    }
-    fn yellow(self) -> Self::Output {
-        colored::Colorize::yellow(self)
-    }
-    fn red(self) -> Self::Output {
-        colored::Colorize::red(self)
-    }
-    fn green(self) -> Self::Output {
-        colored::Colorize::green(self)
-    }
-    fn dimmed(self) -> Self::Output {
-        colored::Colorize::dimmed(self)
-    }
+thread_local! {
+    /// If set all styled text is recorded here instead of being shown with terminal colours
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
 }
 
-#[cfg(not(feature = "colored"))]
-impl Coloured for char {
-    type Output = char;
-    fn blue(self) -> Self::Output {
-        self
-    }
-    fn yellow(self) -> Self::Output {
-        self
-    }
-    fn red(self) -> Self::Output {
-        self
+/// A writer that keeps the current [`Recording`] up to date with the length of the text
+struct Recorder(String);
+
+impl fmt::Write for Recorder {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.push_str(s);
+        RECORDING.with(|r| {
+            if let Some(recording) = r.borrow_mut().as_mut() {
+                recording.written = self.0.len();
+            }
+        });
+        Ok(())
     }
-    fn green(self) -> Self::Output {
-        self
+}
+
+/// Render the given value with the styles of all styled text recorded instead of shown as
+/// terminal colours. Returns the rendered text and the styled byte ranges of that text, sorted by
+/// start with enclosing ranges before the ranges they contain. Text that is styled before it is
+/// written (for example via an intermediate `String`) is not recorded.
+pub(crate) fn record_styles(value: &impl fmt::Display) -> (String, Vec<(Style, Range<usize>)>) {
+    let previous = RECORDING.with(|r| {
+        r.replace(Some(Recording {
+            written: 0,
+            runs: Vec::new(),
+        }))
+    });
+    let mut recorder = Recorder(String::new());
+    // Writing to a String cannot fail
+    let _ = fmt::write(&mut recorder, format_args!("{value}"));
+    let recording = RECORDING.with(|r| r.replace(previous));
+    let mut runs = recording.map_or_else(Vec::new, |r| r.runs);
+    runs.sort_by_key(|(_, range)| (range.start, std::cmp::Reverse(range.end)));
+    (recorder.0, runs)
+}
+
+/// Render the given value into a `String` to be written later with [`write_buffered`], keeping
+/// the styled ranges if styles are being recorded (see [`record_styles`]).
+pub(crate) fn buffer(value: &impl fmt::Display) -> (String, Vec<(Style, Range<usize>)>) {
+    if recorded_position().is_some() {
+        record_styles(value)
+    } else {
+        (value.to_string(), Vec::new())
     }
-    fn dimmed(self) -> Self::Output {
-        self
+}
+
+/// Write text rendered with [`buffer`], recording its styled ranges at their place in the output
+/// if styles are being recorded.
+pub(crate) fn write_buffered(
+    f: &mut impl fmt::Write,
+    text: &str,
+    runs: &[(Style, Range<usize>)],
+) -> fmt::Result {
+    let start = recorded_position();
+    f.write_str(text)?;
+    if let Some(start) = start {
+        RECORDING.with(|r| {
+            if let Some(recording) = r.borrow_mut().as_mut() {
+                recording.runs.extend(
+                    runs.iter()
+                        .map(|(style, range)| (*style, range.start + start..range.end + start)),
+                );
+            }
+        });
+    }
+    Ok(())
+}
+
+/// The number of bytes written so far if styles are being recorded, see [`record_styles`]
+fn recorded_position() -> Option<usize> {
+    RECORDING.with(|r| r.borrow().as_ref().map(|r| r.written))
+}
+
+/// Check if styled text is shown with its style, which needs colours to be allowed and either
+/// the styles to be recorded or the `colored` feature
+pub(crate) fn styles_shown() -> bool {
+    crate::DisplayConfig::with_current(|config| config.colour)
+        && (cfg!(feature = "colored") || recorded_position().is_some())
+}
+
+/// A value with a style applied, the style is only applied on display.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Styled<T> {
+    value: T,
+    style: Style,
+}
+
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !crate::DisplayConfig::with_current(|config| config.colour) {
+            self.value.fmt(f)
+        } else if let Some(start) = recorded_position() {
+            self.value.fmt(f)?;
+            RECORDING.with(|r| {
+                if let Some(recording) = r.borrow_mut().as_mut() {
+                    if recording.written > start {
+                        recording.runs.push((self.style, start..recording.written));
+                    }
+                }
+            });
+            Ok(())
+        } else {
+            #[cfg(feature = "colored")]
+            {
+                let text = self.value.to_string();
+                let coloured = match self.style {
+                    Style::Blue => colored::Colorize::blue(text.as_str()),
+                    Style::Yellow => colored::Colorize::yellow(text.as_str()),
+                    Style::Red => colored::Colorize::red(text.as_str()),
+                    Style::Green => colored::Colorize::green(text.as_str()),
+                    Style::Magenta => colored::Colorize::magenta(text.as_str()),
+                    Style::Cyan => colored::Colorize::cyan(text.as_str()),
+                    Style::Dimmed => colored::Colorize::dimmed(text.as_str()),
+                    Style::Bold => colored::Colorize::bold(text.as_str()),
+                    Style::Background => colored::Colorize::reversed(text.as_str()),
+                };
+                fmt::Display::fmt(&coloured, f)
+            }
+            #[cfg(not(feature = "colored"))]
+            {
+                self.value.fmt(f)
+            }
+        }
     }
 }
 
-#[cfg(feature = "colored")]
-impl Coloured for char {
-    type Output = colored::ColoredString;
-    fn blue(self) -> Self::Output {
-        colored::Colorize::blue(self.to_string().as_str())
+pub(crate) trait Coloured: Sized {
+    fn styled(self, style: Style) -> Styled<Self> {
+        Styled { value: self, style }
     }
-    fn yellow(self) -> Self::Output {
-        colored::Colorize::yellow(self.to_string().as_str())
+    fn blue(self) -> Styled<Self> {
+        self.styled(Style::Blue)
     }
-    fn red(self) -> Self::Output {
-        colored::Colorize::red(self.to_string().as_str())
+    fn yellow(self) -> Styled<Self> {
+        self.styled(Style::Yellow)
     }
-    fn green(self) -> Self::Output {
-        colored::Colorize::green(self.to_string().as_str())
+    fn red(self) -> Styled<Self> {
+        self.styled(Style::Red)
     }
-    fn dimmed(self) -> Self::Output {
-        colored::Colorize::dimmed(self.to_string().as_str())
+    fn green(self) -> Styled<Self> {
+        self.styled(Style::Green)
+    }
+    fn dimmed(self) -> Styled<Self> {
+        self.styled(Style::Dimmed)
     }
 }
+
+impl Coloured for String {}
+impl Coloured for &str {}
+impl Coloured for char {}
@@ -1,13 +1,107 @@
-use std::marker::PhantomData;
+use std::{borrow::Cow, cmp::Ordering, marker::PhantomData};
 
 use crate::{CreateError, ErrorKind, FullErrorContent};
 
+/// Settings to loosen the requirements for merging errors, by default errors only merge if all
+/// properties except the contexts are identical.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct MergeSettings {
+    /// Merge errors that only differ in their version, the version of the first error is kept
+    pub(crate) ignore_version: bool,
+    /// Merge errors that only differ in their suggestions, the suggestions are combined
+    pub(crate) ignore_suggestions: bool,
+    /// Compare the descriptions and suggestions case insensitively and after NFC normalisation
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) normalise_text: bool,
+}
+
+impl MergeSettings {
+    /// Merge errors that only differ in their version, the version of the first error is kept
+    #[must_use]
+    pub const fn ignore_version(self, ignore_version: bool) -> Self {
+        Self {
+            ignore_version,
+            ..self
+        }
+    }
+
+    /// Merge errors that only differ in their suggestions, the suggestions are combined
+    #[must_use]
+    pub const fn ignore_suggestions(self, ignore_suggestions: bool) -> Self {
+        Self {
+            ignore_suggestions,
+            ..self
+        }
+    }
+
+    /// Compare the descriptions and suggestions case insensitively, so errors created from
+    /// differently written input still merge. With the `unicode-normalization` feature the texts
+    /// are also compared after NFC normalisation, so visually identical texts with differently
+    /// composed characters merge as well. When suggestions are combined the suggestions that are
+    /// the same after normalisation are only kept once.
+    /// ```
+    /// use context_error::*;
+    /// let error = |description| BoxedError::small(BasicKind::Error, description, "");
+    /// let settings = MergeSettings::default().normalise_text(true);
+    /// let mut errors = vec![error("Invalid Number")];
+    /// combine_error_with(&mut errors, error("invalid number"), settings);
+    /// assert_eq!(errors.len(), 1);
+    /// ```
+    #[must_use]
+    pub const fn normalise_text(self, normalise_text: bool) -> Self {
+        Self {
+            normalise_text,
+            ..self
+        }
+    }
+
+    /// Get the text as it should be compared with these settings
+    pub(crate) fn normalise<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !self.normalise_text {
+            Cow::Borrowed(text)
+        } else {
+            #[cfg(feature = "unicode-normalization")]
+            {
+                use unicode_normalization::UnicodeNormalization;
+                Cow::Owned(text.nfc().flat_map(char::to_lowercase).collect())
+            }
+            #[cfg(not(feature = "unicode-normalization"))]
+            Cow::Owned(text.to_lowercase())
+        }
+    }
+}
+
 /// Combine a new error into a stack of existing errors. This merges errors that can be merged
 /// to be able to show a terser error if the same error happened multiple times in the same file.
 pub fn combine_error<'a, E: CreateError<'a, Kind>, Kind: ErrorKind>(errors: &mut Vec<E>, error: E) {
+    combine_error_with(errors, error, MergeSettings::default());
+}
+
+/// Combine a new error into a stack of existing errors, with the given settings to loosen the
+/// requirements for merging.
+pub fn combine_error_with<'a, E: CreateError<'a, Kind>, Kind: ErrorKind>(
+    errors: &mut Vec<E>,
+    error: E,
+    settings: MergeSettings,
+) {
     for e in &mut *errors {
-        if FullErrorContent::could_merge(e, &error) {
+        if FullErrorContent::could_merge_with(e, &error, settings) {
             e.add_contexts_ref(error.get_contexts().iter().cloned());
+            if settings.ignore_suggestions {
+                let present: Vec<_> = e
+                    .get_suggestions()
+                    .iter()
+                    .map(|s| settings.normalise(s).into_owned())
+                    .collect();
+                let new: Vec<_> = error
+                    .get_suggestions()
+                    .iter()
+                    .filter(|s| !present.iter().any(|p| *p == settings.normalise(s)))
+                    .cloned()
+                    .collect();
+                *e = std::mem::take(e).suggestions(new);
+            }
             return;
         }
     }
@@ -18,9 +112,123 @@ pub fn combine_error<'a, E: CreateError<'a, Kind>, Kind: ErrorKind>(errors: &mut
 pub fn combine_errors<'a, E: CreateError<'a, Kind>, Kind: ErrorKind>(
     base_errors: &mut Vec<E>,
     new_errors: impl IntoIterator<Item = E>,
+) {
+    combine_errors_with(base_errors, new_errors, MergeSettings::default());
+}
+
+/// Combine a list full of error into the list of already existing errors, with the given settings
+/// to loosen the requirements for merging.
+pub fn combine_errors_with<'a, E: CreateError<'a, Kind>, Kind: ErrorKind>(
+    base_errors: &mut Vec<E>,
+    new_errors: impl IntoIterator<Item = E>,
+    settings: MergeSettings,
 ) {
     for e in new_errors {
-        combine_error(base_errors, e);
+        combine_error_with(base_errors, e, settings);
+    }
+}
+
+/// Sort errors by the location of their first context, see [`crate::Context::cmp_location`].
+/// Errors without any context are placed last. The sort is stable, so errors at the same location
+/// keep their order.
+pub fn sort_by_location<'a, E: FullErrorContent<'a, Kind>, Kind: ErrorKind>(errors: &mut [E]) {
+    errors.sort_by(|a, b| cmp_first_location(&a.get_contexts(), &b.get_contexts()));
+}
+
+/// Dampen noisy kinds of errors, so that a report dominated by one kind does not bury the other
+/// errors. Of every kind with more than `limit` errors only the first `keep` errors are kept, the
+/// others are replaced by a single error of the same kind at the place of the first replaced
+/// error that says how many errors were suppressed. Use this after combining the errors (see
+/// [`combine_errors`]), so errors that can be merged are counted once.
+/// ```
+/// use context_error::*;
+/// let warning = |line| BoxedError::new(
+///     BasicKind::Warning,
+///     "Trailing whitespace",
+///     format!("Line {line} ends with spaces"),
+///     Context::default().line_index(line),
+/// );
+/// let mut errors: Vec<_> = (0..5).map(warning).collect();
+/// errors.push(BoxedError::message(BasicKind::Error, "Unclosed delimiter"));
+/// dampen_errors(&mut errors, 3, 2);
+/// assert_eq!(
+///     errors.iter().map(|e| e.get_short_description()).collect::<Vec<_>>(),
+///     [
+///         "Trailing whitespace",
+///         "Trailing whitespace",
+///         "3 further 'Trailing whitespace' warnings suppressed",
+///         "Unclosed delimiter",
+///     ]
+/// );
+/// ```
+pub fn dampen_errors<'a, E: CreateError<'a, Kind>, Kind: ErrorKind>(
+    errors: &mut Vec<E>,
+    limit: usize,
+    keep: usize,
+) {
+    // The kinds with the number of errors and the title of the suppressed errors, if they all
+    // have the same title
+    let mut kinds: Vec<(Kind, usize, Option<String>, bool)> = Vec::new();
+    for error in errors.iter() {
+        let kind = error.get_kind();
+        let index = kinds
+            .iter()
+            .position(|(k, ..)| *k == kind)
+            .unwrap_or_else(|| {
+                kinds.push((kind, 0, None, true));
+                kinds.len() - 1
+            });
+        let (_, count, title, same) = &mut kinds[index];
+        *count += 1;
+        if *count > keep {
+            let short = error.short_description_ref();
+            match title {
+                Some(title) => *same &= *title == short,
+                None => *title = Some(short.into_owned()),
+            }
+        }
+    }
+    if kinds.iter().all(|(_, count, ..)| *count <= limit) {
+        return;
+    }
+    let mut seen = vec![0; kinds.len()];
+    for error in std::mem::take(errors) {
+        let kind = error.get_kind();
+        let index = kinds
+            .iter()
+            .position(|(k, ..)| *k == kind)
+            .expect("All kinds were counted");
+        seen[index] += 1;
+        let (kind, count, title, same) = &kinds[index];
+        if *count <= limit || seen[index] <= keep {
+            errors.push(error);
+        } else if seen[index] == keep + 1 {
+            let suppressed = count - keep;
+            let plural = if suppressed == 1 { "" } else { "s" };
+            let descriptor = kind.descriptor();
+            errors.push(E::message(
+                error.get_kind(),
+                match title.as_deref().filter(|_| *same) {
+                    Some(title) => {
+                        format!("{suppressed} further '{title}' {descriptor}{plural} suppressed")
+                    }
+                    None => format!("{suppressed} further {descriptor}{plural} suppressed"),
+                },
+            ));
+        }
+    }
+}
+
+/// Compare the locations of the first contexts of two errors, errors without any context last
+pub(crate) fn cmp_first_location(a: &[crate::Context<'_>], b: &[crate::Context<'_>]) -> Ordering {
+    match (
+        a.iter().min_by(|x, y| x.cmp_location(y)),
+        b.iter().min_by(|x, y| x.cmp_location(y)),
+    ) {
+        (Some(a), Some(b)) => a.cmp_location(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
     }
 }
 
@@ -91,4 +299,156 @@ where
     pub fn errors(&self) -> &[E] {
         &self.errors
     }
+
+    /// Take the combined errors, any items that were not yet iterated over are dropped
+    pub fn into_errors(self) -> Vec<E> {
+        self.errors
+    }
+
+    /// Consume all remaining items and return all successful values and the combined errors
+    /// ```
+    /// use context_error::*;
+    /// let (numbers, errors) = ["1", "2", "x", "3", "y"]
+    ///     .iter()
+    ///     .map(|s| s.parse::<u8>().map_err(|_| BoxedError::small(BasicKind::Error, "Invalid number", "")))
+    ///     .combine_errors()
+    ///     .finish();
+    /// assert_eq!(numbers, [1, 2, 3]);
+    /// assert_eq!(errors.len(), 1);
+    /// ```
+    pub fn finish(mut self) -> (Vec<T>, Vec<E>) {
+        let values = (&mut self).collect();
+        (values, self.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicKind, BoxedError, Context, CreateError, CustomError, StaticErrorContent};
+
+    #[test]
+    fn adapter() {
+        let results = [Ok(1), Err("a"), Ok(2), Err("b"), Err("a")]
+            .map(|r| r.map_err(|text| BoxedError::small(BasicKind::Error, "Invalid", text)));
+        let mut adapter = results.clone().into_iter().combine_errors();
+        assert_eq!((&mut adapter).next(), Some(1));
+        assert_eq!(adapter.into_errors().len(), 0);
+        let (values, errors) = results.into_iter().combine_errors().finish();
+        assert_eq!(values, [1, 2]);
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn merge_settings() {
+        let error = |line: u64, version: &'static str, suggestion: &'static str| {
+            CustomError::new(
+                BasicKind::Error,
+                "Invalid number",
+                "Not a number",
+                Context::default().line_index(line).lines(0, "80o0"),
+            )
+            .version(version)
+            .suggestions([suggestion])
+        };
+        let mut errors = Vec::new();
+        combine_errors(&mut errors, [error(1, "v1", "a"), error(2, "v2", "b")]);
+        assert_eq!(errors.len(), 2);
+
+        let mut errors = Vec::new();
+        combine_errors_with(
+            &mut errors,
+            [error(1, "v1", "a"), error(2, "v2", "b")],
+            MergeSettings::default()
+                .ignore_version(true)
+                .ignore_suggestions(true),
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].get_contexts().len(), 2);
+        assert_eq!(errors[0].get_version(), "v1");
+        assert_eq!(errors[0].get_suggestions().as_ref(), ["a", "b"]);
+    }
+
+    #[test]
+    fn normalise_text() {
+        let error = |description: &'static str, suggestion: &'static str| {
+            CustomError::small(BasicKind::Error, description, "").suggestions([suggestion])
+        };
+        let settings = MergeSettings::default()
+            .ignore_suggestions(true)
+            .normalise_text(true);
+        let mut errors = vec![error("Unknown name", "Zoë")];
+        combine_errors_with(&mut errors, [error("unknown NAME", "zoë")], settings);
+        combine_errors_with(&mut errors, [error("Unknown name", "Zoe")], settings);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].get_suggestions().as_ref(), ["Zoë", "Zoe"]);
+        #[cfg(feature = "unicode-normalization")]
+        {
+            combine_errors_with(&mut errors, [error("Unknown name", "Zoe\u{308}")], settings);
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].get_suggestions().as_ref(), ["Zoë", "Zoe"]);
+        }
+        assert!(!FullErrorContent::could_merge(
+            &error("Unknown name", "a"),
+            &error("unknown name", "A")
+        ));
+        assert!(FullErrorContent::could_merge_with(
+            &error("Unknown name", "a"),
+            &error("unknown name", "A"),
+            MergeSettings::default().normalise_text(true)
+        ));
+    }
+
+    #[test]
+    fn sort_location() {
+        let error =
+            |context: Context<'static>| CustomError::new(BasicKind::Error, "test", "test", context);
+        let mut errors = vec![
+            error(Context::default()),
+            error(Context::default().source("b.txt")),
+            error(Context::default().source("a.txt").line_index(10)),
+            error(Context::default().source("a.txt").line_index(2)),
+        ];
+        sort_by_location(&mut errors);
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| e.get_contexts()[0].location_string())
+                .collect::<Vec<_>>(),
+            ["a.txt:3", "a.txt:11", "b.txt", ""]
+        );
+    }
+
+    #[test]
+    fn dampen() {
+        let error = |kind, title: &'static str| CustomError::small(kind, title, "");
+        let titles = |errors: &[CustomError<'_, BasicKind>]| {
+            errors
+                .iter()
+                .map(|e| e.get_short_description().into_owned())
+                .collect::<Vec<_>>()
+        };
+        let mut errors = vec![
+            error(BasicKind::Warning, "a"),
+            error(BasicKind::Error, "x"),
+            error(BasicKind::Warning, "b"),
+            error(BasicKind::Warning, "c"),
+            error(BasicKind::Error, "y"),
+        ];
+        let original = errors.clone();
+        dampen_errors(&mut errors, 3, 0);
+        assert_eq!(errors, original);
+        dampen_errors(&mut errors, 2, 1);
+        assert_eq!(
+            titles(&errors),
+            ["a", "x", "2 further warnings suppressed", "y"]
+        );
+        assert_eq!(errors[2].get_kind(), BasicKind::Warning);
+        let mut errors = original;
+        dampen_errors(&mut errors, 1, 2);
+        assert_eq!(
+            titles(&errors),
+            ["a", "x", "b", "1 further 'c' warning suppressed", "y"]
+        );
+    }
 }
@@ -0,0 +1,113 @@
+use std::{borrow::Cow, fmt, hash::Hash, ops::Deref, sync::Arc};
+
+/// The text of a context, this can be borrowed, owned, or shared between multiple contexts. The
+/// shared variant keeps [`crate::Context::to_owned`] cheap for contexts that point at the same text.
+#[derive(Clone)]
+pub(crate) enum ContextText<'text> {
+    Borrowed(&'text str),
+    Owned(String),
+    Shared(Arc<str>),
+}
+
+impl ContextText<'_> {
+    /// (Possibly) clone the text to get a static valid text, shared text is never cloned
+    pub(crate) fn into_owned(self) -> ContextText<'static> {
+        match self {
+            Self::Borrowed(s) => ContextText::Owned(s.to_string()),
+            Self::Owned(s) => ContextText::Owned(s),
+            Self::Shared(s) => ContextText::Shared(s),
+        }
+    }
+
+    /// Convert the text into shared text, which can be cloned cheaply
+    pub(crate) fn into_shared(self) -> ContextText<'static> {
+        match self {
+            Self::Shared(s) => ContextText::Shared(s),
+            other => ContextText::Shared(Arc::from(&*other)),
+        }
+    }
+}
+
+impl Default for ContextText<'_> {
+    fn default() -> Self {
+        Self::Borrowed("")
+    }
+}
+
+impl Deref for ContextText<'_> {
+    type Target = str;
+    fn deref(&self) -> &str {
+        match self {
+            Self::Borrowed(s) => s,
+            Self::Owned(s) => s,
+            Self::Shared(s) => s,
+        }
+    }
+}
+
+impl AsRef<str> for ContextText<'_> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<'text> From<Cow<'text, str>> for ContextText<'text> {
+    fn from(value: Cow<'text, str>) -> Self {
+        match value {
+            Cow::Borrowed(s) => Self::Borrowed(s),
+            Cow::Owned(s) => Self::Owned(s),
+        }
+    }
+}
+
+impl From<Arc<str>> for ContextText<'_> {
+    fn from(value: Arc<str>) -> Self {
+        Self::Shared(value)
+    }
+}
+
+impl fmt::Debug for ContextText<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl PartialEq for ContextText<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for ContextText<'_> {}
+
+impl Hash for ContextText<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl PartialOrd for ContextText<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ContextText<'_> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ContextText<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ContextText<'_> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::Owned)
+    }
+}
@@ -1,6 +1,9 @@
 use std::{borrow::Cow, error, fmt};
 
-use crate::{BoxedError, Context, CreateError, ErrorKind, FullErrorContent, StaticErrorContent};
+use crate::{
+    BasicKind, BoxedError, Context, CreateError, ErrorKind, ErrorSummary, FullErrorContent,
+    StaticErrorContent, VersionMismatch,
+};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -13,8 +16,14 @@ pub struct CustomError<'text, Kind> {
     pub(crate) long_description: Cow<'text, str>,
     /// Possible suggestion(s) for the indicated text
     pub(crate) suggestions: Vec<Cow<'text, str>>,
+    /// Notes with additional remarks, shown below the long description
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) notes: Vec<Cow<'text, str>>,
     /// Version if applicable
     pub(crate) version: Cow<'text, str>,
+    /// The expected version if this error is about an unsupported version
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) expected_version: Option<Cow<'text, str>>,
     /// The context, in the most general sense this produces output which leads the user to the right place in the code or file
     pub(crate) contexts: Vec<Context<'text>>,
     /// Underlying errors
@@ -41,6 +50,45 @@ impl<'text, Kind: 'text> StaticErrorContent<'text> for CustomError<'text, Kind>
     fn get_version(&self) -> Cow<'text, str> {
         self.version.clone()
     }
+
+    /// Borrows the short description
+    fn short_description_ref<'a>(&'a self) -> Cow<'a, str>
+    where
+        'text: 'a,
+    {
+        Cow::Borrowed(&self.short_description)
+    }
+
+    /// Borrows the long description
+    fn long_description_ref<'a>(&'a self) -> Cow<'a, str>
+    where
+        'text: 'a,
+    {
+        Cow::Borrowed(&self.long_description)
+    }
+
+    /// Borrows the version
+    fn version_ref<'a>(&'a self) -> Cow<'a, str>
+    where
+        'text: 'a,
+    {
+        Cow::Borrowed(&self.version)
+    }
+
+    /// The notes
+    fn get_notes<'a>(&'a self) -> Cow<'a, [Cow<'text, str>]> {
+        Cow::Borrowed(self.notes.as_slice())
+    }
+
+    /// The expected and found version
+    fn get_version_mismatch(&self) -> Option<VersionMismatch> {
+        self.expected_version
+            .as_ref()
+            .map(|expected| VersionMismatch {
+                expected: expected.to_string(),
+                found: self.version.to_string(),
+            })
+    }
 }
 
 impl<'text, Kind: 'text + Clone + PartialEq + ErrorKind> FullErrorContent<'text, Kind>
@@ -85,13 +133,24 @@ impl<'text, Kind: ErrorKind + 'text + Clone> CreateError<'text, Kind> for Custom
         }
     }
 
-    /// Extend the suggestions with the given suggestions, does not remove any previously added suggestions
+    /// Extend the suggestions with the given suggestions, does not remove any previously added suggestions.
+    /// Suggestions that are already present are skipped.
     fn suggestions(
         mut self,
         suggestions: impl IntoIterator<Item = impl Into<Cow<'text, str>>>,
     ) -> Self {
-        self.suggestions
-            .extend(suggestions.into_iter().map(|s| s.into()));
+        for suggestion in suggestions {
+            let suggestion = suggestion.into();
+            if !self.suggestions.contains(&suggestion) {
+                self.suggestions.push(suggestion);
+            }
+        }
+        self
+    }
+
+    /// Add the given notes, will append to the current list.
+    fn notes(mut self, notes: impl IntoIterator<Item = impl Into<Cow<'text, str>>>) -> Self {
+        self.notes.extend(notes.into_iter().map(Into::into));
         self
     }
 
@@ -103,6 +162,14 @@ impl<'text, Kind: ErrorKind + 'text + Clone> CreateError<'text, Kind> for Custom
         }
     }
 
+    /// Set the expected version
+    fn expected_version(self, expected: impl Into<Cow<'text, str>>) -> Self {
+        Self {
+            expected_version: Some(expected.into()),
+            ..self
+        }
+    }
+
     /// Update with a new context
     fn replace_context(self, context: Context<'text>) -> Self {
         Self {
@@ -148,7 +215,7 @@ impl<'text, Kind: ErrorKind + 'text + Clone> CreateError<'text, Kind> for Custom
     }
 
     /// Set the context line index
-    fn overwrite_line_index(self, line_index: u32) -> Self {
+    fn overwrite_line_index(self, line_index: u64) -> Self {
         Self {
             contexts: self
                 .contexts
@@ -158,9 +225,47 @@ impl<'text, Kind: ErrorKind + 'text + Clone> CreateError<'text, Kind> for Custom
             ..self
         }
     }
+
+    /// Transform the short description
+    fn map_short_description(self, f: impl FnOnce(Cow<'text, str>) -> Cow<'text, str>) -> Self {
+        Self {
+            short_description: f(self.short_description),
+            ..self
+        }
+    }
+
+    /// Transform the long description
+    fn map_long_description(self, f: impl FnOnce(Cow<'text, str>) -> Cow<'text, str>) -> Self {
+        Self {
+            long_description: f(self.long_description),
+            ..self
+        }
+    }
+
+    /// Transform all contexts
+    fn map_contexts(self, f: impl FnMut(Context<'text>) -> Context<'text>) -> Self {
+        Self {
+            contexts: self.contexts.into_iter().map(f).collect(),
+            ..self
+        }
+    }
 }
 
 impl<'text, Kind: ErrorKind> CustomError<'text, Kind> {
+    /// Get a copy of this error with the text of all contexts redacted, including the contexts
+    /// of the underlying errors, see [`Context::redact`]. The descriptions and suggestions are
+    /// kept as is.
+    pub fn redact(&self) -> CustomError<'static, Kind>
+    where
+        Kind: Clone,
+    {
+        CustomError {
+            contexts: self.contexts.iter().map(Context::redact).collect(),
+            underlying_errors: self.underlying_errors.iter().map(Self::redact).collect(),
+            ..self.clone().to_owned()
+        }
+    }
+
     /// (Possibly) clone the text to get a static valid error
     pub fn to_owned(self) -> CustomError<'static, Kind> {
         CustomError {
@@ -171,7 +276,15 @@ impl<'text, Kind: ErrorKind> CustomError<'text, Kind> {
                 .into_iter()
                 .map(|p| Cow::Owned(p.into_owned()))
                 .collect(),
+            notes: self
+                .notes
+                .into_iter()
+                .map(|n| Cow::Owned(n.into_owned()))
+                .collect(),
             version: Cow::Owned(self.version.into_owned()),
+            expected_version: self
+                .expected_version
+                .map(|expected| Cow::Owned(expected.into_owned())),
             contexts: self.contexts.into_iter().map(|c| c.to_owned()).collect(),
             underlying_errors: self
                 .underlying_errors
@@ -183,19 +296,98 @@ impl<'text, Kind: ErrorKind> CustomError<'text, Kind> {
     }
 }
 
+impl<Kind: ErrorKind> ErrorSummary for CustomError<'_, Kind> {
+    fn kind_descriptor(&self) -> &'static str {
+        self.kind.descriptor()
+    }
+
+    fn title(&self) -> &str {
+        &self.short_description
+    }
+
+    fn description(&self) -> &str {
+        &self.long_description
+    }
+
+    fn contexts(&self) -> &[Context<'_>] {
+        &self.contexts
+    }
+}
+
 impl<Kind: ErrorKind + Clone> fmt::Debug for CustomError<'_, Kind> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.display(f, None, true)
+        fmt_error(self, f, None)
     }
 }
 
 impl<Kind: ErrorKind + Clone> fmt::Display for CustomError<'_, Kind> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.display(f, None, true)
+        fmt_error(self, f, None)
     }
 }
 
-impl<Kind: ErrorKind + Clone> error::Error for CustomError<'_, Kind> {}
+/// Display an error, with the alternate flag (`{:#}` or `{:#?}`) the error is displayed with
+/// [`crate::Verbosity::Verbose`] so no lines are trimmed. The width and precision of the formatter
+/// are applied as well, see [`crate::DisplayConfig::width`].
+///
+/// If [`crate::DisplayConfig::catch_panics`] is set a panic while rendering, for example on a bug
+/// in the layout of exotic input, shows the error as a plain fallback instead with the panic
+/// message to include in a bug report. The panic hook is still called for the panic.
+pub(crate) fn fmt_error<'text, Kind: ErrorKind>(
+    error: &impl FullErrorContent<'text, Kind>,
+    f: &mut fmt::Formatter<'_>,
+    settings: Option<Kind::Settings>,
+) -> fmt::Result {
+    let config = crate::DisplayConfig::current();
+    let verbose = f.alternate();
+    let config = if verbose {
+        config.verbosity(crate::Verbosity::Verbose)
+    } else {
+        config
+    }
+    .for_formatter(f);
+    if !config.catch_panics {
+        return config.scoped(|| {
+            write!(
+                f,
+                "{}",
+                crate::DisplayFn(|f: &mut fmt::Formatter<'_>| {
+                    error.display(f, settings.clone(), !verbose)
+                })
+            )
+        });
+    }
+    // The error is rendered in full before writing, so a panic does not leave half an error
+    let rendered = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        config.scoped(|| {
+            crate::buffer(&crate::DisplayFn(|f: &mut fmt::Formatter<'_>| {
+                error.display(f, settings.clone(), !verbose)
+            }))
+        })
+    }));
+    match rendered {
+        Ok((text, runs)) => crate::write_buffered(f, &text, &runs),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+            write!(
+                f,
+                "{}: {}\n<error could not be rendered: {message}>\n\n",
+                error.get_kind().descriptor(),
+                error.short_description_ref(),
+            )
+        }
+    }
+}
+
+impl<Kind: ErrorKind + Clone> error::Error for CustomError<'_, Kind> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.kind.source_error()
+    }
+}
 
 impl<'text, Kind: ErrorKind> From<BoxedError<'text, Kind>> for CustomError<'text, Kind> {
     fn from(value: BoxedError<'text, Kind>) -> Self {
@@ -203,10 +395,39 @@ impl<'text, Kind: ErrorKind> From<BoxedError<'text, Kind>> for CustomError<'text
     }
 }
 
+/// Create an error of kind [`BasicKind::Error`] with the message as short description, see
+/// [`CreateError::message`]
+impl<'text> From<&'text str> for CustomError<'text, BasicKind> {
+    fn from(value: &'text str) -> Self {
+        Self::message(BasicKind::Error, value)
+    }
+}
+
+/// Create an error of kind [`BasicKind::Error`] with the message as short description, see
+/// [`CreateError::message`]
+impl From<String> for CustomError<'_, BasicKind> {
+    fn from(value: String) -> Self {
+        Self::message(BasicKind::Error, value)
+    }
+}
+
+/// Create an error of kind [`BasicKind::Error`] with the IO error as short description, see
+/// [`CreateError::from_display`]. This allows `?` to be used on IO results in functions returning
+/// errors of this type.
+impl From<std::io::Error> for CustomError<'_, BasicKind> {
+    fn from(value: std::io::Error) -> Self {
+        Self::from_display(BasicKind::Error, &value)
+    }
+}
+
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use super::*;
-    use crate::{BasicKind, FilePosition};
+    use crate::{
+        test_fixtures::{error, TestKind},
+        BasicKind, DisplayConfig, FilePosition, ResultExt,
+    };
 
     macro_rules! test {
         ($name:ident: $error:expr => $expected:expr) => {
@@ -214,7 +435,7 @@ mod tests {
             fn $name() {
                 let error = $error;
                 let string = error.to_string();
-                #[cfg(not(feature="ascii-only"))]
+                #[cfg(not(any(feature="ascii-only", windows)))]
                 if string != $expected {
                     panic!("Generated error:\n{}\nNot identical to expected:\n{}\nThis is the generated if this actually is correct: {0:?}", string, $expected);
                 }
@@ -232,6 +453,8 @@ mod tests {
         => "error: Invalid path\n ╷\n │ fileee.txt\n ╵\nThis file does not exist\nDid you mean: file.txt?\n");
     test!(suggestions: CustomError::new(BasicKind::Error, "Invalid path", "This file does not exist", Context::show("fileee.txt")).suggestions(["file.txt", "filet.txt"]) 
         => "error: Invalid path\n ╷\n │ fileee.txt\n ╵\nThis file does not exist\nDid you mean any of: file.txt, filet.txt?\n");
+    test!(notes: CustomError::new(BasicKind::Error, "Invalid path", "This file does not exist", Context::show("fileee.txt")).suggestions(["file.txt"]).notes(["paths are relative to the project", "hidden files are skipped"]).add_underlying_error(CustomError::message(BasicKind::Warning, "test"))
+        => "error: Invalid path\n ╷\n │ fileee.txt\n ╵\nThis file does not exist\nnote: paths are relative to the project\nnote: hidden files are skipped\nDid you mean: file.txt?\nUnderlying error:\nwarning: test\n\n");
     test!(version: CustomError::new(BasicKind::Error, "Invalid number", "This columns is not a number", Context::default().lines(0, "null,80o0,YES,,67.77").add_highlight((0, 5..9))).version("Software AB v2025.42") 
         => "error: Invalid number\n ╷\n │ null,80o0,YES,,67.77\n ╎      ╶──╴\n ╵\nThis columns is not a number\nVersion: Software AB v2025.42\n");
     test!(merged: CustomError::new(BasicKind::Error, "Invalid number", "This columns is not a number", Context::default().line_index(2).lines(0, "null,80o0,YES,,67.77").add_highlight((0, 5..9)))
@@ -254,9 +477,603 @@ mod tests {
         assert!(!a.get_kind().is_error(()));
     }
 
+    #[test]
+    fn message() {
+        let error: CustomError<BasicKind> = "Missing input".into();
+        assert_eq!(
+            error,
+            CustomError::new(BasicKind::Error, "Missing input", "", Context::default())
+        );
+        let error: BoxedError<BasicKind> = format!("Missing {}", "output").into();
+        assert_eq!(error.get_short_description(), "Missing output");
+        assert_eq!(error.get_kind(), BasicKind::Error);
+        assert!(error.get_contexts().iter().all(Context::is_empty));
+    }
+
+    #[test]
+    fn redact() {
+        let error = BoxedError::new(
+            BasicKind::Error,
+            "Invalid number",
+            "test",
+            Context::default()
+                .lines(0, "secret 80o0")
+                .add_highlight((0, 7..11)),
+        )
+        .add_underlying_error(CustomError::new(
+            BasicKind::Error,
+            "Inner",
+            "test",
+            Context::default().source("secret.txt").lines(0, "password"),
+        ));
+        let redacted = error.redact();
+        assert_eq!(redacted.get_contexts()[0].get_lines(), "****** ****");
+        assert_eq!(redacted.get_contexts()[0].get_highlights().len(), 1);
+        let underlying = &redacted.get_underlying_errors()[0];
+        assert_eq!(underlying.get_contexts()[0].get_lines(), "********");
+        assert_eq!(
+            underlying.get_contexts()[0].get_source(),
+            Some("secret.txt")
+        );
+        assert!(!redacted.to_string().contains("secret 80o0"));
+    }
+
+    #[test]
+    fn kind_source() {
+        use std::error::Error;
+        let io = TestKind {
+            descriptor: "error",
+            code: Some("io"),
+            ..TestKind::default()
+        };
+        let error = BoxedError::message(crate::SourceKind(io), "Could not read file");
+        assert_eq!(error.to_string(), "error[io]: Could not read file\n\n");
+        assert_eq!(
+            error.source().map(ToString::to_string).as_deref(),
+            Some("io")
+        );
+        assert!(CustomError::message(BasicKind::Error, "test")
+            .source()
+            .is_none());
+    }
+
+    #[test]
+    fn kind_colour() {
+        let deprecation = TestKind {
+            descriptor: "deprecation",
+            colour: Some(crate::Color::Magenta),
+            ..TestKind::default()
+        };
+        let context = Context::default()
+            .lines(0, "old syntax")
+            .add_highlight((0, 0..3));
+        let svg = error(deprecation.clone(), "Old", context.clone())
+            .add_underlying_error(error(deprecation, "Older", context))
+            .to_svg(true);
+        assert!(svg.contains("<tspan class='magenta'>deprecation</tspan>: Old"));
+        assert_eq!(svg.matches("<tspan class='magenta'>").count(), 4);
+        assert!(svg.contains("<tspan class='yellow'>Underlying error</tspan>"));
+        assert_eq!(svg.matches("<tspan class='yellow'>").count(), 1);
+    }
+
+    #[test]
+    fn svg() {
+        let svg = CustomError::new(
+            BasicKind::Error,
+            "Invalid <tag>",
+            "test",
+            Context::default()
+                .lines(0, "a & b")
+                .add_highlight((0, 2, 1)),
+        )
+        .to_svg(true);
+        assert!(svg.starts_with("<svg xmlns='http://www.w3.org/2000/svg'"));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains("<tspan class='red'>error</tspan>: Invalid &lt;tag&gt;"));
+        assert!(svg.contains("a &amp; b"));
+        assert_eq!(svg.matches("<text ").count(), 6);
+        assert_eq!(
+            svg.matches("<tspan").count(),
+            svg.matches("</tspan>").count()
+        );
+    }
+
+    #[test]
+    fn svg_private_use_text() {
+        let error = CustomError::new(
+            BasicKind::Error,
+            "Invalid \u{F8FF}",
+            "test",
+            Context::default()
+                .lines(0, "\u{F8F0}0a\u{F8FF} b")
+                .add_highlight((0, 4, 1)),
+        );
+        let svg = crate::DisplayConfig::default()
+            .unicode(true)
+            .scoped(|| error.to_svg(true));
+        assert!(svg.contains("<tspan class='red'>error</tspan>: Invalid \u{F8FF}"));
+        assert!(svg.contains("\u{F8F0}0a\u{F8FF} b"));
+        assert_eq!(
+            svg.matches("<tspan").count(),
+            svg.matches("</tspan>").count()
+        );
+    }
+
+    #[test]
+    fn compact() {
+        assert_eq!(
+            CustomError::message(BasicKind::Warning, "Unused").to_compact(),
+            "warning: Unused"
+        );
+        assert_eq!(
+            CustomError::new(
+                BasicKind::Error,
+                "Missing file",
+                "",
+                Context::default().source("a.txt")
+            )
+            .to_compact(),
+            "error: Missing file\n  at a.txt"
+        );
+        let line = format!("{}value{}", "a".repeat(100), "b".repeat(100));
+        let error = CustomError::new(
+            BasicKind::Error,
+            "Invalid",
+            "",
+            Context::default()
+                .line_index(0)
+                .lines(0, line)
+                .add_highlight((0, 100..105)),
+        );
+        crate::DisplayConfig::default().width(40).scoped(|| {
+            assert_eq!(
+                error.to_compact(),
+                format!(
+                    "error: Invalid\n  {}value{}\n  {}^^^^^\n  at :1:101-105",
+                    "a".repeat(9),
+                    "b".repeat(24),
+                    " ".repeat(9)
+                )
+            );
+        });
+        let error = CustomError::new(
+            BasicKind::Error,
+            "Unknown modification",
+            "",
+            Context::default()
+                .source("peptide.txt")
+                .line_index(0)
+                .lines(0, "A[deamidation]")
+                .add_highlight((0, 2..)),
+        );
+        assert_eq!(
+            error.to_compact(),
+            "error: Unknown modification\n  A[deamidation]\n    ^^^^^^^^^^^^\n  at peptide.txt:1:3-14"
+        );
+    }
+
+    #[test]
+    fn formatter_parameters() {
+        let line = format!("{}value{}", "a".repeat(100), "b".repeat(100));
+        let error = CustomError::new(
+            BasicKind::Error,
+            "Invalid",
+            "Not a value",
+            Context::default()
+                .line_index(0)
+                .lines(0, line)
+                .add_highlight((0, 100..105)),
+        );
+        let narrow = crate::DisplayConfig::default()
+            .width(40)
+            .scoped(|| error.to_string());
+        assert_eq!(format!("{error:40}"), narrow);
+        assert_ne!(format!("{error}"), narrow);
+        assert_eq!(format!("{error:.0}"), "error: Invalid\nNot a value\n");
+        assert_eq!(format!("{:.0}", error.get_contexts()[0]), "[:1]");
+        let report: crate::Report<_, BasicKind> = crate::Report::new([error.clone(), error]);
+        assert_eq!(
+            format!("{report:.0}"),
+            "error: Invalid\nNot a value\n\nerror: Invalid\nNot a value\n"
+        );
+    }
+
+    #[test]
+    fn aligned_underlying_gutters() {
+        let error = |line| {
+            CustomError::new(
+                BasicKind::Error,
+                "Invalid",
+                "",
+                Context::default().line_index(line).lines(0, "80o0"),
+            )
+        };
+        let string = crate::DisplayConfig::default()
+            .unicode(false)
+            .scoped(|| error(8).add_underlying_error(error(1233)).to_string());
+        assert_eq!(
+            string,
+            "error: Invalid\n     .\n9    | 80o0\n     '\n\nUnderlying error:\n\
+            error: Invalid\n     .\n1234 | 80o0\n     '\n\n"
+        );
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let context = Context::default().line_index(2).lines(0, "format: v3");
+        let error = CustomError::version_mismatch(BasicKind::Error, "v2", "v3", context.clone());
+        assert_eq!(
+            error.get_contexts()[0].get_highlights(),
+            [crate::Highlight::from((0, 8, 2, "expected v2"))]
+        );
+        assert_eq!(
+            error.get_version_mismatch(),
+            Some(crate::VersionMismatch {
+                expected: "v2".to_string(),
+                found: "v3".to_string(),
+            })
+        );
+        let highlighted = context.clone().add_highlight((0, 0..6));
+        let error = BoxedError::version_mismatch(BasicKind::Error, "v2", "v3", highlighted.clone());
+        assert_eq!(
+            error.get_contexts()[0].get_highlights(),
+            highlighted.get_highlights()
+        );
+        assert_eq!(error.get_version(), "v3");
+        let error = CustomError::version_mismatch(BasicKind::Error, "v2", "v4", context.clone());
+        assert!(error.get_contexts()[0].get_highlights().is_empty());
+        assert_eq!(error.get_version_mismatch().unwrap().found, "v4");
+        let translated = error
+            .map_long_description(|_| Cow::Borrowed("Versie v4 wordt niet ondersteund"))
+            .convert::<BasicKind, BoxedError<'_, BasicKind>>(|kind| kind);
+        assert_eq!(translated.get_version_mismatch().unwrap().expected, "v2");
+        assert_eq!(
+            CustomError::new(BasicKind::Error, "test", "test", context.clone())
+                .get_version_mismatch(),
+            None
+        );
+        let wording = CustomError::new(
+            BasicKind::Error,
+            "test",
+            "Expected version v2, found version v3",
+            context,
+        );
+        assert_eq!(wording.version("v3").get_version_mismatch(), None);
+    }
+
+    #[test]
+    fn html_severity() {
+        let html =
+            CustomError::new(BasicKind::Error, "test", "test", Context::default()).to_html(true);
+        assert!(html.starts_with("<div class='ce-error ce-kind-error ce-severity-error'>"));
+        let mut html = String::new();
+        CustomError::new(BasicKind::Warning, "test", "test", Context::default())
+            .display_html(&mut html, Some(()), true)
+            .unwrap();
+        assert!(html.starts_with("<div class='ce-error ce-kind-warning ce-severity-warning'>"));
+        let mut html = String::new();
+        CustomError::new(BasicKind::Warning, "test", "test", Context::default())
+            .add_underlying_error(CustomError::message(BasicKind::Error, "cause"))
+            .display_html(&mut html, Some(()), true)
+            .unwrap();
+        assert!(html.starts_with("<div class='ce-error ce-kind-warning ce-severity-error'>"));
+        crate::DisplayConfig::default()
+            .html(crate::HtmlSettings::default().compat(true))
+            .scoped(|| {
+                assert!(
+                    CustomError::new(BasicKind::Error, "test", "test", Context::default())
+                        .to_html(true)
+                        .starts_with("<div class='context-error error severity-error'>")
+                );
+            });
+    }
+
+    #[test]
+    fn highlight_links() {
+        let error = CustomError::new(
+            BasicKind::Error,
+            "Mismatched types",
+            "",
+            Context::default()
+                .line_index(9)
+                .lines(0, "let y: u8 = x;")
+                .add_highlight(crate::Highlight::from((0, 12..13)).link(1))
+                .add_highlight(crate::Highlight::from((0, 4..5)).link(0)),
+        )
+        .add_context(Context::default().line_index(1).lines(0, "let x = 1;"));
+        let text = crate::DisplayConfig::default()
+            .unicode(false)
+            .scoped(|| error.to_string());
+        assert!(text.contains("--> :2\n"), "{text}");
+        let html = error.to_html(true);
+        let ids: Vec<_> = html
+            .split("id='")
+            .skip(1)
+            .filter_map(|rest| rest.split_once('\''))
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids[0].starts_with("ce-context-") && ids[0].ends_with("-0"));
+        assert!(html.contains(&format!("<a class='ce-link' href='#{}'>→</a>", ids[1])));
+        assert_eq!(html.matches("ce-link").count(), 1);
+        assert!(!CustomError::new(
+            BasicKind::Error,
+            "test",
+            "",
+            Context::default().lines(0, "a")
+        )
+        .to_html(true)
+        .contains(" id='"));
+    }
+
+    #[test]
+    fn combinators() {
+        let result: Result<(), CustomError<'_, BasicKind>> = Err(CustomError::new(
+            BasicKind::Error,
+            "Invalid number",
+            "test",
+            Context::default().lines(0, "80o0"),
+        ));
+        let error = result
+            .map_err_contexts(|c| c.source("file.csv"))
+            .map_err_short_description(|s| format!("{s} in column 2").into())
+            .unwrap_err()
+            .with_suggestion_if(false, "8000")
+            .with_suggestion_if(true, "8010");
+        assert_eq!(error.get_short_description(), "Invalid number in column 2");
+        assert_eq!(error.get_contexts()[0].get_source(), Some("file.csv"));
+        assert_eq!(error.get_suggestions().as_ref(), ["8010"]);
+    }
+
+    #[test]
+    fn display_config() {
+        let error = CustomError::new(
+            BasicKind::Error,
+            "Invalid number",
+            "This column is not a number",
+            Context::default().lines(0, "null,80o0,YES,,67.77"),
+        )
+        .version("v1");
+        crate::DisplayConfig::default()
+            .verbosity(crate::Verbosity::Short)
+            .set_thread_local();
+        assert_eq!(
+            error.to_string(),
+            "error: Invalid number\nThis column is not a number\n"
+        );
+        crate::DisplayConfig::reset_thread_local();
+        assert!(error.to_string().contains("Version: v1"));
+    }
+
+    #[test]
+    fn alternate() {
+        let line = format!("{}80o0", "a,".repeat(100));
+        let error = CustomError::new(
+            BasicKind::Error,
+            "Invalid number",
+            "This column is not a number",
+            Context::default()
+                .lines(0, line.as_str())
+                .add_highlight((0, 200..204)),
+        );
+        // Without trimming the line is shown from the start (wrapped over multiple lines)
+        let start = &line[..20];
+        assert!(!error.to_string().contains(start));
+        assert!(format!("{error:#}").contains(start));
+        assert!(format!("{error:#?}").contains(start));
+        assert!(format!("{:#}", error.get_contexts()[0]).contains(start));
+        assert_eq!(
+            crate::DisplayConfig::current(),
+            crate::DisplayConfig::default()
+        );
+    }
+
+    #[test]
+    fn max_depth() {
+        let error = (0..4).fold(
+            CustomError::new(BasicKind::Error, "level 4", "", Context::default()),
+            |inner, level| {
+                CustomError::new(
+                    BasicKind::Error,
+                    format!("level {}", 3 - level),
+                    "",
+                    Context::default(),
+                )
+                .add_underlying_error(inner)
+            },
+        );
+        crate::DisplayConfig::default()
+            .max_depth(1)
+            .set_thread_local();
+        assert_eq!(
+            error.to_string(),
+            "error: level 0\n\nUnderlying error:\nerror: level 1\n\n… 3 more levels of underlying errors\n"
+                .replace('…', &crate::DisplayConfig::current().symbols().ellipsis.to_string())
+        );
+        assert!(error.to_string_full_depth().contains("level 4"));
+        assert!(format!("{error:#}").contains("level 4"));
+        crate::DisplayConfig::reset_thread_local();
+        assert!(error.to_string().contains("level 4"));
+    }
+
+    #[test]
+    fn suggestions_deduplicated_and_capped() {
+        let error = CustomError::new(BasicKind::Error, "test", "test", Context::default())
+            .suggestions(["a", "b", "a", "c", "d", "b"]);
+        assert_eq!(error.get_suggestions().as_ref(), ["a", "b", "c", "d"]);
+        crate::DisplayConfig::default()
+            .max_suggestions(2)
+            .set_thread_local();
+        assert_eq!(
+            error.to_string(),
+            "error: test\ntest\nDid you mean any of: a, b, and 2 more?\n"
+        );
+        crate::DisplayConfig::reset_thread_local();
+        assert!(FullErrorContent::could_merge(
+            &error,
+            &CustomError::new(BasicKind::Error, "test", "test", Context::default())
+                .suggestions(["d", "c", "b", "a"])
+        ));
+    }
+
+    #[test]
+    fn suggestion_list() {
+        let long = "a".repeat(30);
+        let error = CustomError::message(BasicKind::Error, "test").suggestions([
+            long.as_str(),
+            "b",
+            "c",
+            "d",
+            "e",
+            "f",
+        ]);
+        let config = crate::DisplayConfig::default().unicode(false);
+        assert_eq!(
+            config
+                .clone()
+                .max_suggestions(3)
+                .scoped(|| error.to_string()),
+            format!(
+                "error: test\n\nDid you mean any of:\n  * {long}\n  * b\n  * c\n  * and 3 more\n"
+            )
+        );
+        let error =
+            CustomError::message(BasicKind::Error, "test").suggestions([long.as_str(), "b"]);
+        assert_eq!(
+            config.clone().scoped(|| error.to_string()),
+            format!("error: test\n\nDid you mean any of: {long}, b?\n")
+        );
+        assert_eq!(
+            config.width(30).scoped(|| error.to_string()),
+            format!("error: test\n\nDid you mean any of:\n  * {long}\n  * b\n")
+        );
+    }
+
+    #[test]
+    fn html_stream() {
+        let errors = [
+            CustomError::new(BasicKind::Error, "a", "test", Context::default()),
+            CustomError::new(BasicKind::Warning, "b", "test", Context::default()),
+        ];
+        let mut buffer = Vec::new();
+        crate::write_html_stream(&mut buffer, errors.iter().cloned(), None, true).unwrap();
+        let streamed = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            streamed,
+            format!(
+                "<div class='ce-errors'>{}{}</div>",
+                errors[0].to_html(true),
+                errors[1].to_html(true)
+            )
+        );
+        let mut buffer = Vec::new();
+        errors[0].write_html(&mut buffer, None, true).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), errors[0].to_html(true));
+        // The writer is flushed, so nothing is left in a buffered writer that is not dropped
+        let mut buffered = std::io::BufWriter::new(Vec::new());
+        errors[0].write_html(&mut buffered, None, true).unwrap();
+        assert_eq!(buffered.get_ref(), errors[0].to_html(true).as_bytes());
+    }
+
     #[test]
     fn test_well_behaved() {
         let a = CustomError::new(BasicKind::Error, "test", "test", Context::none());
         let _io_packaged = std::io::Error::other(a);
     }
+
+    #[test]
+    fn render_panic() {
+        let broken = TestKind {
+            descriptor: "error",
+            panics: true,
+            ..TestKind::default()
+        };
+        let error = CustomError::message(broken, "Invalid number");
+        crate::DisplayConfig::default()
+            .catch_panics(true)
+            .scoped(|| {
+                assert_eq!(
+                    error.to_string(),
+                    "error: Invalid number\n<error could not be rendered: broken code>\n\n"
+                );
+                assert_eq!(
+                    CustomError::message(BasicKind::Error, "Invalid number").to_string(),
+                    "error: Invalid number\n\n"
+                );
+            });
+        let result = std::panic::catch_unwind(|| error.to_string());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn from_io_error() {
+        let error: CustomError<BasicKind> = std::fs::read_to_string("").unwrap_err().into();
+        assert_eq!(error.get_kind(), BasicKind::Error);
+        assert_eq!(
+            error.get_short_description(),
+            std::fs::read("").unwrap_err().to_string()
+        );
+        assert!(error.get_contexts().iter().all(Context::is_empty));
+    }
+
+    #[test]
+    fn occurrence_summary() {
+        let context = |line| {
+            Context::default()
+                .line_index(line)
+                .lines(0, "80o0")
+                .add_highlight((0, 0..4))
+        };
+        let error = CustomError::new(BasicKind::Error, "Invalid number", "", context(0))
+            .add_contexts([context(1), Context::default()]);
+        DisplayConfig::default()
+            .unicode(true)
+            .occurrence_summary(2)
+            .scoped(|| {
+                assert_eq!(
+                    error.to_string(),
+                    "error: Invalid number\n  ╷\n1 │ 80o0\n  ╎ ╶──╴\n2 │ 80o0\n  ╎ ╶──╴\n  ╰─ 2 occurrences\n\n"
+                );
+            });
+        DisplayConfig::default()
+            .occurrence_summary(1)
+            .caret_only(true)
+            .scoped(|| {
+                assert_eq!(
+                    CustomError::new(BasicKind::Error, "Invalid number", "", context(0))
+                        .to_string(),
+                    "error: Invalid number\n80o0\n^~~~\n1 occurrence\n\n"
+                );
+            });
+        DisplayConfig::default()
+            .unicode(true)
+            .occurrence_summary(3)
+            .scoped(|| assert!(!error.to_string().contains("occurrences")));
+    }
+
+    #[test]
+    fn borrowed_accessors() {
+        let error = CustomError::new(
+            BasicKind::Error,
+            String::from("Invalid number"),
+            String::from("Not a number"),
+            Context::default(),
+        )
+        .version(String::from("v2"));
+        assert!(matches!(
+            error.short_description_ref(),
+            Cow::Borrowed("Invalid number")
+        ));
+        assert!(matches!(
+            error.long_description_ref(),
+            Cow::Borrowed("Not a number")
+        ));
+        assert!(matches!(error.version_ref(), Cow::Borrowed("v2")));
+        let boxed = BoxedError::from(error);
+        assert!(matches!(
+            boxed.short_description_ref(),
+            Cow::Borrowed("Invalid number")
+        ));
+        assert!(matches!(boxed.version_ref(), Cow::Borrowed("v2")));
+    }
 }
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+use crate::{Context, ErrorKind, FullErrorContent};
+
+/// Directives in the source to silence errors, like `// context-error: allow(warning)`. A
+/// directive silences the errors of the listed kinds, matched by their descriptor or code (see
+/// [`ErrorKind::code`]), on the line of the directive and the line below it. Scan the source with
+/// [`Self::scan`] and remove the silenced errors with [`Self::remove_silenced`] before combining
+/// the errors.
+/// ```
+/// use context_error::*;
+/// let source = "a = 1\n# context-error: allow(warning)\nb = 2\nc = 3";
+/// let warning = |line| BoxedError::new(
+///     BasicKind::Warning,
+///     "Unused value",
+///     "",
+///     Context::default().line_index(line).lines(0, "x = 1"),
+/// );
+/// let mut errors = vec![warning(0), warning(2), warning(3)];
+/// Directives::default().scan(0, source).remove_silenced(&mut errors);
+/// assert_eq!(
+///     errors.iter().map(|e| e.get_contexts()[0].get_line_index()).collect::<Vec<_>>(),
+///     [Some(0), Some(3)]
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Directives {
+    /// The text that starts a directive
+    prefix: String,
+    /// The allowed kinds per line index
+    allowed: BTreeMap<u64, Vec<String>>,
+}
+
+impl Default for Directives {
+    fn default() -> Self {
+        Self {
+            prefix: "context-error:".to_string(),
+            allowed: BTreeMap::new(),
+        }
+    }
+}
+
+impl Directives {
+    /// Set the text that starts a directive, `context-error:` by default. Only applies to text
+    /// scanned afterwards.
+    #[must_use]
+    pub fn prefix(self, prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            ..self
+        }
+    }
+
+    /// Scan the given text for directives, the first line of the text has the given line index
+    #[must_use]
+    pub fn scan(mut self, first_line_index: u64, text: &str) -> Self {
+        for (index, line) in text.lines().enumerate() {
+            let names = self.parse(line);
+            if !names.is_empty() {
+                let line_index = first_line_index.saturating_add(index as u64);
+                self.allowed.entry(line_index).or_default().extend(names);
+            }
+        }
+        self
+    }
+
+    /// Scan the text of the contexts of the given error for directives, for when the full source
+    /// is not available. Only directives within the text of the contexts are found.
+    #[must_use]
+    pub fn scan_error<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+        self,
+        error: &E,
+    ) -> Self {
+        error
+            .get_contexts()
+            .iter()
+            .fold(self, |directives, context| match context.get_line_index() {
+                Some(line_index) => directives.scan(line_index, context.get_lines()),
+                None => directives,
+            })
+    }
+
+    /// Check if the kind with the given descriptor or code is allowed on the given line, by a
+    /// directive on that line or the line above
+    pub fn allows(&self, line_index: u64, name: &str) -> bool {
+        let allowed_on = |index| {
+            self.allowed
+                .get(&index)
+                .map_or(false, |names| names.iter().any(|n| n == name))
+        };
+        allowed_on(line_index) || line_index.checked_sub(1).map_or(false, allowed_on)
+    }
+
+    /// Check if the given error is silenced, meaning that the kind of the error is allowed at the
+    /// location of all its contexts. Errors without any context with a line are never silenced.
+    pub fn is_silenced<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+        &self,
+        error: &E,
+    ) -> bool {
+        let kind = error.get_kind();
+        let code = kind.code();
+        let contexts = error.get_contexts();
+        let mut lines = contexts.iter().filter_map(location).peekable();
+        lines.peek().is_some()
+            && lines.all(|line| {
+                self.allows(line, kind.descriptor())
+                    || code.as_ref().map_or(false, |code| self.allows(line, code))
+            })
+    }
+
+    /// Remove all silenced errors, see [`Self::is_silenced`]
+    pub fn remove_silenced<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+        &self,
+        errors: &mut Vec<E>,
+    ) {
+        errors.retain(|error| !self.is_silenced(error));
+    }
+
+    /// Get the names in the directives on this line
+    fn parse(&self, line: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        for (start, _) in line.match_indices(&self.prefix) {
+            let rest = line[start + self.prefix.len()..].trim_start();
+            if let Some(list) = rest
+                .strip_prefix("allow(")
+                .and_then(|rest| rest.split_once(')'))
+                .map(|(list, _)| list)
+            {
+                names.extend(
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(ToString::to_string),
+                );
+            }
+        }
+        names
+    }
+}
+
+/// The line index of the primary highlight of the context, or of the context itself
+fn location(context: &Context<'_>) -> Option<u64> {
+    context
+        .get_line_index()
+        .map(|index| index.saturating_add(context.primary_highlight().map_or(0, |h| h.line as u64)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_fixtures::{error, TestKind},
+        BasicKind, CreateError,
+    };
+
+    #[test]
+    fn parse() {
+        let directives = Directives::default().scan(
+            4,
+            "x // context-error: allow(warning, unused)\n\
+            context-error:allow()\n\
+            // context-error: deny(error) context-error: allow( error )",
+        );
+        assert!(directives.allows(4, "warning"));
+        assert!(directives.allows(5, "unused"));
+        assert!(!directives.allows(6, "warning"));
+        assert!(!directives.allows(3, "warning"));
+        assert!(directives.allows(6, "error"));
+        assert!(!Directives::default()
+            .prefix("lint:")
+            .scan(0, "context-error: allow(error)")
+            .allows(0, "error"));
+    }
+
+    #[test]
+    fn silenced() {
+        let kind = TestKind {
+            code: Some("Unused"),
+            ..TestKind::default()
+        };
+        let unused = |context| error(kind.clone(), "Unused", context);
+        let context = Context::default()
+            .line_index(10)
+            .lines(0, "# context-error: allow(Unused)\nx = 1")
+            .add_highlight((1, 0..1));
+        let directives = Directives::default().scan_error(&unused(context.clone()));
+        assert!(!directives.is_silenced(&error(BasicKind::Warning, "Unused", context.clone())));
+        assert!(directives.is_silenced(&unused(context.clone())));
+        assert!(!directives.is_silenced(&unused(Context::default().line_index(20))));
+        assert!(!directives.is_silenced(&unused(Context::default())));
+        assert!(!directives
+            .is_silenced(&unused(context).add_context(Context::default().line_index(20))));
+    }
+}
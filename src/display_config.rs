@@ -0,0 +1,861 @@
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+/// Settings for displaying errors that are consulted by the [`std::fmt::Display`] implementations
+/// of all errors and contexts. Because `Display` cannot take any arguments these can be set
+/// application wide with [`DisplayConfig::set_global`] or for the current thread with
+/// [`DisplayConfig::set_thread_local`] (which takes precedence over the global config).
+/// ```
+/// use context_error::*;
+/// DisplayConfig::default().unicode(false).set_thread_local();
+/// let error = CustomError::new(BasicKind::Error, "Invalid number", "Not a number", Context::default().lines(0, "80o0"));
+/// assert_eq!(error.to_string(), "error: Invalid number\n .\n | 80o0\n '\nNot a number\n");
+/// DisplayConfig::reset_thread_local();
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisplayConfig {
+    /// Allow colours in the output, these are only shown if the `colored` feature is enabled
+    pub(crate) colour: bool,
+    /// The maximal width in columns of the output
+    pub(crate) width: usize,
+    /// The characters used to draw contexts
+    pub(crate) symbol_set: SymbolSet,
+    /// The amount of detail shown
+    pub(crate) verbosity: Verbosity,
+    /// The maximal number of suggestions shown
+    pub(crate) max_suggestions: usize,
+    /// Show the suggestions as a list if there are more than this number of suggestions
+    pub(crate) suggestion_list: usize,
+    /// Show the line numbers in the gutter of contexts
+    pub(crate) line_numbers: bool,
+    /// Show the moment contexts were recorded (only with the `time` feature)
+    pub(crate) timestamps: bool,
+    /// The maximal number of levels of underlying errors shown
+    pub(crate) max_depth: usize,
+    /// The colour of the highlights, set from the kind of the error that is displayed
+    pub(crate) highlight_colour: Option<crate::Color>,
+    /// The version that is shown once for a whole report and so is not shown for every error
+    pub(crate) hoisted_version: Option<String>,
+    /// The source that is shown once for a whole report and so is not shown for every context
+    pub(crate) hoisted_source: Option<String>,
+    /// Show unicode bidirectional formatting characters as the replacement character
+    pub(crate) escape_bidi: bool,
+    /// Force the lines of contexts to be displayed left to right
+    pub(crate) force_ltr: bool,
+    /// The maximal width in columns of highlight comments
+    pub(crate) max_comment_width: usize,
+    /// Show comments longer than the maximal width as footnotes instead of truncating them
+    pub(crate) long_comment_footnotes: bool,
+    /// Show a warning for highlights on lines that are not in the text of their context
+    pub(crate) mark_outside_highlights: bool,
+    /// The minimal number of contexts of an error to end them with a summary of the occurrences
+    pub(crate) occurrence_summary: usize,
+    /// The minimal width of the gutter with line numbers
+    pub(crate) gutter_width: usize,
+    /// Wrap long lines over multiple rows instead of cropping them around the highlights
+    pub(crate) wrap_lines: bool,
+    /// Show contexts as only the highlighted lines with a row of carets below
+    pub(crate) caret_only: bool,
+    /// Style inline markup (`` `code` `` and `**bold**`) in descriptions, notes, and comments
+    pub(crate) inline_markup: bool,
+    /// Show errors that panic while rendering as a plain fallback instead of panicking
+    pub(crate) catch_panics: bool,
+    /// Transform the sources of contexts before they are displayed
+    pub(crate) source_mapper: Option<TextMapper>,
+    /// Transform the lines of text of contexts before they are displayed
+    pub(crate) text_transform: Option<TextMapper>,
+    /// Look up the text of contexts that only point at a location
+    pub(crate) source_resolver: Option<crate::SharedResolver>,
+    /// The class names used in the HTML output
+    pub(crate) html: crate::HtmlSettings,
+}
+
+/// A function to transform text of contexts before it is displayed, see
+/// [`DisplayConfig::source_mapper`] and [`DisplayConfig::text_transform`]. Two mappers are only
+/// equal if they are the same function.
+#[derive(Clone)]
+pub(crate) struct TextMapper(Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl fmt::Debug for TextMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TextMapper")
+    }
+}
+
+impl PartialEq for TextMapper {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for TextMapper {}
+
+/// The set of characters used to draw contexts
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SymbolSet {
+    /// Unicode box drawing characters
+    Unicode,
+    /// Box drawing characters that are present in the common Windows console fonts, this avoids
+    /// the rounded corners, dashed lines, and the zero width marker `ò`, and shows control
+    /// characters as the substitute character. This is the default on Windows.
+    WindowsSafe,
+    /// Only ASCII characters
+    Ascii,
+}
+
+impl Default for SymbolSet {
+    fn default() -> Self {
+        if cfg!(feature = "ascii-only") {
+            Self::Ascii
+        } else if cfg!(windows) {
+            Self::WindowsSafe
+        } else {
+            Self::Unicode
+        }
+    }
+}
+
+/// The amount of detail shown when displaying an error
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Verbosity {
+    /// Only show the title and long description, no contexts, suggestions, version, or underlying errors
+    Short,
+    /// Show all information, but trim long lines in the contexts to only show the highlighted parts
+    #[default]
+    Normal,
+    /// Show all information and never trim the contexts
+    Verbose,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            colour: true,
+            width: 100,
+            symbol_set: SymbolSet::default(),
+            verbosity: Verbosity::Normal,
+            max_suggestions: 10,
+            suggestion_list: 5,
+            line_numbers: true,
+            timestamps: false,
+            max_depth: usize::MAX,
+            highlight_colour: None,
+            hoisted_version: None,
+            hoisted_source: None,
+            escape_bidi: true,
+            force_ltr: false,
+            max_comment_width: usize::MAX,
+            long_comment_footnotes: false,
+            mark_outside_highlights: false,
+            occurrence_summary: usize::MAX,
+            gutter_width: 0,
+            wrap_lines: false,
+            caret_only: false,
+            inline_markup: false,
+            catch_panics: false,
+            source_mapper: None,
+            text_transform: None,
+            source_resolver: None,
+            html: crate::HtmlSettings::default(),
+        }
+    }
+}
+
+static GLOBAL: RwLock<Option<Arc<DisplayConfig>>> = RwLock::new(None);
+
+thread_local! {
+    static THREAD_LOCAL: RefCell<Option<Arc<DisplayConfig>>> = const { RefCell::new(None) };
+}
+
+/// Builder style methods
+impl DisplayConfig {
+    /// Allow or disallow colours (only used if the `colored` feature is enabled)
+    #[must_use]
+    pub fn colour(self, colour: bool) -> Self {
+        Self { colour, ..self }
+    }
+
+    /// Set the maximal width in columns of the output, the minimal supported width is 20.
+    ///
+    /// The layout can also be controlled per call with the standard formatting parameters when
+    /// displaying errors, reports, or contexts. The width (`{:120}`) sets the maximal width of the
+    /// output and a precision of zero (`{:.0}`) leaves out all snippets by showing errors with
+    /// [`Verbosity::Short`] and contexts as only their location. Other precisions are ignored.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().unicode(false).set_thread_local();
+    /// let error = CustomError::new(
+    ///     BasicKind::Error,
+    ///     "Invalid number",
+    ///     "Not a number",
+    ///     Context::default().source("data.csv").line_index(1).lines(0, "80o0"),
+    /// );
+    /// assert_eq!(format!("{error:.0}"), "error: Invalid number\nNot a number\n");
+    /// assert_eq!(format!("{:.0}", error.get_contexts()[0]), "[data.csv:2]");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn width(self, width: usize) -> Self {
+        Self {
+            width: width.max(20),
+            ..self
+        }
+    }
+
+    /// Use unicode box drawing characters or only ASCII characters, this overrules the automatic
+    /// selection of [`SymbolSet::WindowsSafe`] on Windows
+    #[must_use]
+    pub fn unicode(self, unicode: bool) -> Self {
+        Self {
+            symbol_set: if unicode {
+                SymbolSet::Unicode
+            } else {
+                SymbolSet::Ascii
+            },
+            ..self
+        }
+    }
+
+    /// Set the characters used to draw contexts
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().symbol_set(SymbolSet::WindowsSafe).set_thread_local();
+    /// let context = Context::default().source("data.csv").lines(0, "null,80o0").add_highlight((0, 5..9));
+    /// assert_eq!(context.to_string(), " ┌─[data.csv]\n │ null,80o0\n :      └──┘\n ┴");
+    /// DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn symbol_set(self, symbol_set: SymbolSet) -> Self {
+        Self { symbol_set, ..self }
+    }
+
+    /// Set the verbosity
+    #[must_use]
+    pub fn verbosity(self, verbosity: Verbosity) -> Self {
+        Self { verbosity, ..self }
+    }
+
+    /// Set the maximal number of suggestions shown, any further suggestions are summarised as
+    /// 'and N more'
+    #[must_use]
+    pub fn max_suggestions(self, max_suggestions: usize) -> Self {
+        Self {
+            max_suggestions,
+            ..self
+        }
+    }
+
+    /// Show the suggestions as a bulleted list, instead of on one line, if there are more than
+    /// the given number of suggestions (5 by default). The suggestions are always shown as a list
+    /// if they do not fit on one line within the width (see [`Self::width`]).
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().unicode(true).suggestion_list(2).set_thread_local();
+    /// let error = CustomError::message(BasicKind::Error, "Unknown column")
+    ///     .suggestions(["retention_time", "mass_over_charge", "charge"]);
+    /// assert_eq!(error.to_string(), "error: Unknown column
+    ///
+    /// Did you mean any of:
+    ///   • retention_time
+    ///   • mass_over_charge
+    ///   • charge
+    /// ");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn suggestion_list(self, suggestion_list: usize) -> Self {
+        Self {
+            suggestion_list,
+            ..self
+        }
+    }
+
+    /// Show or hide the line numbers (and byte ranges) in the gutter of contexts. Hiding them is
+    /// useful for synthesized text, like a constructed query, where line numbers are meaningless.
+    /// The gutter is then only as wide as the box drawing characters.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().unicode(true).line_numbers(false).set_thread_local();
+    /// let context = Context::default().line_index(41).lines(0, "SELECT * FROM users").add_highlight((0, 14..19));
+    /// assert_eq!(context.to_string(), " ╷\n │ SELECT * FROM users\n ╎               ╶───╴\n ╵");
+    /// DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn line_numbers(self, line_numbers: bool) -> Self {
+        Self {
+            line_numbers,
+            ..self
+        }
+    }
+
+    /// Show the moment each context was recorded (in UTC) as a dimmed line below the header of
+    /// the context. This only has an effect if the `time` feature is enabled, which records the
+    /// moment an error is created (see [`crate::Context::timestamp`]).
+    #[must_use]
+    pub fn timestamps(self, timestamps: bool) -> Self {
+        Self { timestamps, ..self }
+    }
+
+    /// Set the maximal number of levels of underlying errors shown, deeper levels are summarised
+    /// as '… 4 more levels of underlying errors'. The full chain is still shown with
+    /// [`Verbosity::Verbose`] (or the alternate flag `{:#}`) and
+    /// [`crate::FullErrorContent::to_string_full_depth`]. By default all levels are shown.
+    #[must_use]
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    /// Show unicode bidirectional formatting characters (like the right-to-left override) in the
+    /// text of contexts as the replacement character `�`. These characters change the order in
+    /// which text is displayed, so they can be used to hide code ('Trojan Source') and they
+    /// misalign the highlights. This is enabled by default.
+    #[must_use]
+    pub fn escape_bidi(self, escape_bidi: bool) -> Self {
+        Self {
+            escape_bidi,
+            ..self
+        }
+    }
+
+    /// Force every line of the text of contexts to be displayed left to right, by surrounding it
+    /// with a left-to-right override. This keeps the highlights aligned with right-to-left text
+    /// (like Arabic or Hebrew) in terminals that support bidirectional text, at the cost of
+    /// showing the right-to-left text in logical instead of reading order.
+    #[must_use]
+    pub fn force_ltr(self, force_ltr: bool) -> Self {
+        Self { force_ltr, ..self }
+    }
+
+    /// Set the maximal width in columns of highlight comments, longer comments are truncated with
+    /// an ellipsis or shown as footnote below the context (see
+    /// [`Self::long_comment_footnotes`]). By default comments are never truncated.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().unicode(true).max_comment_width(10).set_thread_local();
+    /// let context = Context::default().lines(0, "80o0").add_highlight((0, 0..4, "This column should only contain numbers"));
+    /// assert_eq!(context.to_string(), " ╷\n │ 80o0\n ╎ ╶──╴This colu…\n ╵");
+    /// DisplayConfig::default().unicode(true).max_comment_width(10).long_comment_footnotes(true).set_thread_local();
+    /// assert_eq!(context.to_string(), " ╷\n │ 80o0\n ╎ ╶──╴¹\n ╎ ¹ This column should only contain numbers\n ╵");
+    /// DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn max_comment_width(self, max_comment_width: usize) -> Self {
+        Self {
+            max_comment_width: max_comment_width.max(1),
+            ..self
+        }
+    }
+
+    /// Show comments longer than the maximal comment width (see [`Self::max_comment_width`]) as
+    /// numbered footnotes below the context instead of truncating them
+    #[must_use]
+    pub fn long_comment_footnotes(self, long_comment_footnotes: bool) -> Self {
+        Self {
+            long_comment_footnotes,
+            ..self
+        }
+    }
+
+    /// Show a warning row in a context for every highlight on a line that is not in the text of
+    /// the context, instead of silently leaving the highlight out. This is off by default, turn it
+    /// on (for example in tests) to notice highlights that are lost during development. See
+    /// [`crate::Context::validate`] to check the highlights of a context explicitly.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().unicode(true).mark_outside_highlights(true).set_thread_local();
+    /// let context = Context::default().lines(0, "id,value\nnull,80o0").add_highlight((2, 5..9));
+    /// assert_eq!(
+    ///     context.to_string(),
+    ///     " ╷\n │ id,value\n │ null,80o0\n ╎ ⚠ highlight outside snippet, on line 3 of 2\n ╵"
+    /// );
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn mark_outside_highlights(self, mark_outside_highlights: bool) -> Self {
+        Self {
+            mark_outside_highlights,
+            ..self
+        }
+    }
+
+    /// End the contexts of errors with at least the given number of contexts, for example errors
+    /// that were merged from many occurrences, with a dimmed summary of the number of occurrences
+    /// and files instead of a bare end cap (off by default). Contexts that are not shown are not
+    /// counted.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().unicode(true).occurrence_summary(3).set_thread_local();
+    /// let context = |source, line| Context::default().source(source).line_index(line).lines(0, "80o0").add_highlight((0, 0..4));
+    /// let error: BoxedError<BasicKind> = BoxedError::new(BasicKind::Error, "Invalid number", "", context("a.csv", 1))
+    ///     .add_contexts([context("a.csv", 4), context("b.csv", 2)]);
+    /// assert_eq!(error.to_string(), "error: Invalid number
+    ///   ╭─[a.csv:2:1-4]
+    /// 2 │ 80o0
+    ///   ╎ ╶──╴
+    /// 5 │ 80o0
+    ///   ╎ ╶──╴
+    /// 3 │ 80o0
+    ///   ╎ ╶──╴
+    ///   ╰─ 3 occurrences in 2 files
+    ///
+    /// ");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn occurrence_summary(self, min_contexts: usize) -> Self {
+        Self {
+            occurrence_summary: min_contexts,
+            ..self
+        }
+    }
+
+    /// Set the minimal width (in columns) of the gutter with the line numbers, so that the
+    /// gutters of contexts with few digits in their line numbers line up with the gutters of
+    /// other contexts. The gutter is still made wider if a line number does not fit. See
+    /// [`crate::Report::align_gutters`] to align all gutters in a report.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().unicode(true).gutter_width(3).set_thread_local();
+    /// let context = Context::default().line_index(8).lines(0, "null,80o0").add_highlight((0, 5..9));
+    /// assert_eq!(context.to_string(), "    ╷\n9   │ null,80o0\n    ╎      ╶──╴\n    ╵");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn gutter_width(self, gutter_width: usize) -> Self {
+        Self {
+            gutter_width,
+            ..self
+        }
+    }
+
+    /// Show long lines in full by wrapping them over multiple rows, with a marker at the end of
+    /// every wrapped row and the start of every continued row. By default long lines are cropped
+    /// around the highlights, only showing a few characters of context.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().unicode(true).width(25).wrap_lines(true).set_thread_local();
+    /// let context = Context::default().lines(0, "id,name,value,unit,comment").add_highlight((0, 8..13));
+    /// assert_eq!(
+    ///     context.to_string(),
+    ///     " ╷\n │ id,name,value,unit,co↩\n ╎         ╶───╴\n │ ↪mment\n ╵"
+    /// );
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn wrap_lines(self, wrap_lines: bool) -> Self {
+        Self { wrap_lines, ..self }
+    }
+
+    /// Show contexts in a minimal form without gutters, sources, or colours, only the highlighted
+    /// lines (or all lines if there are no highlights) each followed by a row of carets under
+    /// the highlights with their comments. Contexts without text are not shown. This is useful to
+    /// embed errors in the output of other tools, where the full rendering is too noisy.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().caret_only(true).set_thread_local();
+    /// let context = Context::default()
+    ///     .source("data.csv")
+    ///     .line_index(8)
+    ///     .lines(0, "id,value\nnull,80o0")
+    ///     .add_highlight((1, 5..9, "not a number"));
+    /// assert_eq!(context.to_string(), "null,80o0\n     ^~~~ not a number");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn caret_only(self, caret_only: bool) -> Self {
+        Self { caret_only, ..self }
+    }
+
+    /// Style lightweight inline markup in the long descriptions, notes, and highlight comments of
+    /// errors, so important tokens stand out without writing terminal escape codes that would
+    /// leak into other outputs. Text between backticks (`` `code` ``) is shown in cyan and text
+    /// between double asterisks (`**bold**`) in bold. In HTML these become `<code>` and `<strong>`
+    /// elements. If colours are not shown the text is kept as written.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().inline_markup(true).set_thread_local();
+    /// let error = CustomError::new(BasicKind::Error, "Invalid number", "Use `0o` for **octal** numbers", Context::default());
+    /// assert!(FullErrorContent::to_html(&error, true)
+    ///     .contains("Use <code>0o</code> for <strong>octal</strong> numbers"));
+    /// DisplayConfig::default().inline_markup(true).colour(false).set_thread_local();
+    /// assert!(error.to_string().ends_with("Use `0o` for **octal** numbers\n"));
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn inline_markup(self, inline_markup: bool) -> Self {
+        Self {
+            inline_markup,
+            ..self
+        }
+    }
+
+    /// Show errors that panic while rendering, for example on a bug in the layout of exotic
+    /// input, as a plain fallback with the panic message instead of panicking, so displaying an
+    /// error never takes down the application. The panic hook is still called for the panic.
+    /// This renders every error into an intermediate `String` before it is written.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default().catch_panics(true).set_thread_local();
+    /// let error = CustomError::message(BasicKind::Error, "Invalid number");
+    /// assert_eq!(error.to_string(), "error: Invalid number\n\n");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn catch_panics(self, catch_panics: bool) -> Self {
+        Self {
+            catch_panics,
+            ..self
+        }
+    }
+
+    /// Transform the sources of contexts before they are displayed, for example to strip a
+    /// workspace prefix or to map paths in a container to paths on the host. This only changes
+    /// the displayed sources, the sources stored in the contexts are unchanged.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default()
+    ///     .source_mapper(|source| source.strip_prefix("/build/").unwrap_or(source).to_string())
+    ///     .set_thread_local();
+    /// let context = Context::default().source("/build/src/data.csv").line_index(1);
+    /// assert_eq!(context.to_string(), "[src/data.csv:2]");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn source_mapper(self, mapper: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            source_mapper: Some(TextMapper(Arc::new(mapper))),
+            ..self
+        }
+    }
+
+    /// Remove the transformation of sources set with [`Self::source_mapper`]
+    #[must_use]
+    pub fn no_source_mapper(self) -> Self {
+        Self {
+            source_mapper: None,
+            ..self
+        }
+    }
+
+    /// Look up the text of contexts that only point at a location (a source with a line index or
+    /// byte range) when they are displayed, see [`crate::Context::resolve`]. Contexts that already
+    /// have text are shown unchanged.
+    /// ```
+    /// use context_error::*;
+    /// use std::collections::HashMap;
+    /// let sources = HashMap::from([("data.csv".to_string(), "id,value\n1,80o0".to_string())]);
+    /// DisplayConfig::default().unicode(false).source_resolver(sources).set_thread_local();
+    /// let context = Context::default().source("data.csv").byte_range(11..15);
+    /// assert_eq!(context.to_string(), "  +-[data.csv:2:3-6][B:11-15]\n2 | 1,80o0\n  *   ----\n  '");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn source_resolver(
+        self,
+        resolver: impl crate::SourceResolver + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            source_resolver: Some(crate::SharedResolver(Arc::new(resolver))),
+            ..self
+        }
+    }
+
+    /// Remove the source resolver set with [`Self::source_resolver`]
+    #[must_use]
+    pub fn no_source_resolver(self) -> Self {
+        Self {
+            source_resolver: None,
+            ..self
+        }
+    }
+
+    /// Transform the lines of text of contexts before they are displayed in text, HTML, or any
+    /// other rendering, for example to mask secrets like passwords and API keys. The function is
+    /// called for every line separately. To keep the highlights aligned the transformed line
+    /// should have the same number of characters, otherwise it is cut off or padded with spaces.
+    /// The text stored in the contexts is unchanged.
+    /// ```
+    /// use context_error::*;
+    /// DisplayConfig::default()
+    ///     .unicode(false)
+    ///     .text_transform(|line| match line.split_once("password=") {
+    ///         Some((key, value)) => format!("{key}password={}", "*".repeat(value.chars().count())),
+    ///         None => line.to_string(),
+    ///     })
+    ///     .set_thread_local();
+    /// let context = Context::default().lines(0, "user=admin password=hunter2").add_highlight((0, 20..27));
+    /// assert_eq!(context.to_string(), " .\n | user=admin password=*******\n *                     -------\n '");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn text_transform(
+        self,
+        transform: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            text_transform: Some(TextMapper(Arc::new(transform))),
+            ..self
+        }
+    }
+
+    /// Remove the transformation of text set with [`Self::text_transform`]
+    #[must_use]
+    pub fn no_text_transform(self) -> Self {
+        Self {
+            text_transform: None,
+            ..self
+        }
+    }
+
+    /// Set the class names used in the HTML output, see [`crate::HtmlSettings`]
+    #[must_use]
+    pub fn html(self, html: crate::HtmlSettings) -> Self {
+        Self { html, ..self }
+    }
+}
+
+/// Functionality
+impl DisplayConfig {
+    /// Get the config in use on this thread (the thread local config, otherwise the global config,
+    /// otherwise the default config).
+    pub fn current() -> Self {
+        Self::with_current(Clone::clone)
+    }
+
+    /// Look at the current config without cloning it. The config is taken out of the thread local
+    /// cell or global lock before `f` is called, so callbacks run during rendering can change the
+    /// config (for example with [`Self::set_thread_local`]) without panicking or deadlocking.
+    pub(crate) fn with_current<T>(f: impl FnOnce(&Self) -> T) -> T {
+        let config = THREAD_LOCAL
+            .with(|local| local.borrow().clone())
+            .or_else(|| {
+                GLOBAL
+                    .read()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .clone()
+            });
+        match config {
+            Some(config) => f(&config),
+            None => f(&Self::default()),
+        }
+    }
+
+    /// Use this config for all threads that do not have a thread local config
+    pub fn set_global(self) {
+        *GLOBAL
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Arc::new(self));
+    }
+
+    /// Remove the global config, after which the default config is used
+    pub fn reset_global() {
+        *GLOBAL
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+    }
+
+    /// Use this config for the current thread, this overrules the global config
+    pub fn set_thread_local(self) {
+        THREAD_LOCAL.with(|local| *local.borrow_mut() = Some(Arc::new(self)));
+    }
+
+    /// Remove the thread local config, after which the global config is used
+    pub fn reset_thread_local() {
+        THREAD_LOCAL.with(|local| *local.borrow_mut() = None);
+    }
+
+    /// Run the given function with this config as the thread local config, the previous thread
+    /// local config is restored afterwards (also if the function panics)
+    pub(crate) fn scoped<T>(self, f: impl FnOnce() -> T) -> T {
+        struct Restore(Option<Arc<DisplayConfig>>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                THREAD_LOCAL.with(|local| *local.borrow_mut() = previous);
+            }
+        }
+        let _restore =
+            Restore(THREAD_LOCAL.with(|local| local.borrow_mut().replace(Arc::new(self))));
+        f()
+    }
+
+    /// Apply the width and precision of the formatter to this config, see [`Self::width`]
+    pub(crate) fn for_formatter(self, f: &fmt::Formatter<'_>) -> Self {
+        let config = match f.width() {
+            Some(width) => self.width(width),
+            None => self,
+        };
+        if f.precision() == Some(0) {
+            config.verbosity(Verbosity::Short)
+        } else {
+            config
+        }
+    }
+
+    /// Transform the source with the source mapper of the current config, the mapper is called
+    /// outside of the config lock so it can use the config itself
+    pub(crate) fn map_source(source: &str) -> Cow<'_, str> {
+        Self::with_current(|config| config.source_mapper.clone())
+            .map_or(Cow::Borrowed(source), |mapper| {
+                Cow::Owned((mapper.0)(source))
+            })
+    }
+
+    /// Transform the text of a context with the text transform of this config line by line, with
+    /// every transformed line cut off or padded to the number of characters of the original line
+    pub(crate) fn transform_text(&self, text: &str) -> Option<String> {
+        let transform = self.text_transform.as_ref()?;
+        Some(
+            text.split('\n')
+                .map(|line| {
+                    let length = line.chars().count();
+                    let transformed = (transform.0)(line);
+                    let mut aligned: String = transformed.chars().take(length).collect();
+                    aligned.extend(
+                        std::iter::repeat(' ').take(length.saturating_sub(aligned.chars().count())),
+                    );
+                    aligned
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+
+    /// The style of the highlights in contexts
+    pub(crate) fn highlight_style(&self) -> crate::Style {
+        self.highlight_colour
+            .map_or(crate::Style::Yellow, Into::into)
+    }
+
+    /// The symbols to use for drawing contexts
+    pub(crate) const fn symbols(&self) -> &'static Symbols {
+        match self.symbol_set {
+            SymbolSet::Unicode => &Symbols::UNICODE,
+            SymbolSet::WindowsSafe => &Symbols::WINDOWS_SAFE,
+            SymbolSet::Ascii => &Symbols::ASCII,
+        }
+    }
+}
+
+/// The symbols used to draw the contexts
+#[derive(Debug)]
+pub(crate) struct Symbols {
+    pub(crate) highlight_start_line: &'static str,
+    pub(crate) arc_bottom_to_right: char,
+    pub(crate) arc_top_to_right: char,
+    pub(crate) left_to_right: &'static str,
+    pub(crate) top_endcap: char,
+    pub(crate) right_endcap: char,
+    pub(crate) left_endcap: char,
+    pub(crate) bottom_endcap: char,
+    pub(crate) top_to_bottom: char,
+    pub(crate) ellipsis: char,
+    /// Shown at the end of a row of a wrapped line
+    pub(crate) wrap_end: char,
+    /// Shown at the start of a continued row of a wrapped line
+    pub(crate) wrap_start: char,
+    pub(crate) length_zero_highlight: char,
+    pub(crate) length_one_highlight: char,
+    pub(crate) range_indication: char,
+    /// Starts an item of a list, like the suggestions
+    pub(crate) bullet: char,
+    /// The start, end, and single character of a highlight drawn as box
+    pub(crate) box_highlight: (char, char, char),
+    /// Points at the start of a highlight drawn as pointer
+    pub(crate) pointer: char,
+    /// Marks the location of the context a highlight links to
+    pub(crate) link: &'static str,
+    /// Starts a warning about the context itself, like a highlight outside of the text
+    pub(crate) warning: char,
+    /// Show control characters as unicode control pictures, otherwise they are shown as the
+    /// ASCII substitute character
+    pub(crate) control_pictures: bool,
+    /// Show footnote markers as superscript numbers, otherwise they are shown as `[1]`
+    pub(crate) superscripts: bool,
+    /// Show all non ASCII characters of the text as the ASCII substitute character
+    pub(crate) ascii_text: bool,
+}
+
+impl Symbols {
+    pub(crate) const UNICODE: Self = Self {
+        highlight_start_line: " ╎ ",
+        arc_bottom_to_right: '╭',
+        arc_top_to_right: '╰',
+        left_to_right: "─",
+        top_endcap: '╷',
+        right_endcap: '╴',
+        left_endcap: '╶',
+        bottom_endcap: '╵',
+        top_to_bottom: '│',
+        ellipsis: '…',
+        wrap_end: '↩',
+        wrap_start: '↪',
+        length_zero_highlight: 'ò',
+        length_one_highlight: '⁃',
+        range_indication: '—',
+        bullet: '•',
+        box_highlight: ('╰', '╯', '⎵'),
+        pointer: '^',
+        link: "→",
+        warning: '⚠',
+        control_pictures: true,
+        superscripts: true,
+        ascii_text: false,
+    };
+
+    pub(crate) const WINDOWS_SAFE: Self = Self {
+        highlight_start_line: " : ",
+        arc_bottom_to_right: '┌',
+        arc_top_to_right: '└',
+        left_to_right: "─",
+        top_endcap: '┬',
+        right_endcap: '┘',
+        left_endcap: '└',
+        bottom_endcap: '┴',
+        top_to_bottom: '│',
+        ellipsis: '…',
+        wrap_end: '→',
+        wrap_start: '→',
+        length_zero_highlight: '^',
+        length_one_highlight: '^',
+        range_indication: '—',
+        bullet: '•',
+        box_highlight: ('└', '┘', '┴'),
+        pointer: '^',
+        link: "→",
+        warning: '!',
+        control_pictures: false,
+        superscripts: false,
+        ascii_text: false,
+    };
+
+    pub(crate) const ASCII: Self = Self {
+        highlight_start_line: " * ",
+        arc_bottom_to_right: '+',
+        arc_top_to_right: '+',
+        left_to_right: "-",
+        top_endcap: '.',
+        right_endcap: '-',
+        left_endcap: '-',
+        bottom_endcap: '\'',
+        top_to_bottom: '|',
+        ellipsis: '~',
+        wrap_end: '\\',
+        wrap_start: '\\',
+        length_zero_highlight: '^',
+        length_one_highlight: '-',
+        range_indication: '-',
+        bullet: '*',
+        box_highlight: ('[', ']', '='),
+        pointer: '^',
+        link: "->",
+        warning: '!',
+        control_pictures: false,
+        superscripts: false,
+        ascii_text: true,
+    };
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+
+use crate::{BoxedError, CustomError, ErrorKind, ErrorSummary, FullErrorContent};
+
+/// An object safe version of the error content traits, to store errors of different types or
+/// with different kinds together, for example as `Vec<Box<dyn DynError>>`. This gives access to
+/// all content of an error and can display the error in text (with [`fmt::Display`]) and HTML.
+/// ```
+/// use context_error::*;
+/// #[derive(Clone, Debug, Default, PartialEq)]
+/// struct Deprecation;
+/// impl ErrorKind for Deprecation {
+///     type Settings = ();
+///     fn descriptor(&self) -> &'static str { "deprecation" }
+///     fn is_error(&self, _settings: ()) -> bool { false }
+///     fn ignored(&self, _settings: ()) -> bool { false }
+/// }
+/// let errors: Vec<Box<dyn DynError>> = vec![
+///     Box::new(BoxedError::new(BasicKind::Error, "Invalid number", "Not a number", Context::default())),
+///     Box::new(CustomError::new(Deprecation, "Old syntax", "Use the new syntax", Context::default())),
+/// ];
+/// assert_eq!(
+///     errors.iter().map(|e| e.kind_descriptor()).collect::<Vec<_>>(),
+///     ["error", "deprecation"]
+/// );
+/// assert_eq!(errors[1].to_string(), "deprecation: Old syntax\nUse the new syntax\n");
+/// ```
+pub trait DynError: ErrorSummary + std::error::Error {
+    /// The suggestions of this error
+    fn suggestions(&self) -> Vec<&str>;
+
+    /// The notes of this error
+    fn notes(&self) -> Vec<&str>;
+
+    /// The version of this error
+    fn version(&self) -> &str;
+
+    /// The underlying errors of this error
+    fn underlying_errors(&self) -> Vec<&dyn DynError>;
+
+    /// Display this error in HTML
+    /// # Errors
+    /// If the underlying writer errors.
+    fn display_html(&self, f: &mut dyn fmt::Write, allow_trim_context: bool) -> fmt::Result;
+
+    /// Display this error in HTML as a string
+    fn to_html(&self, allow_trim_context: bool) -> String {
+        let mut string = String::new();
+        self.display_html(&mut string, allow_trim_context)
+            .expect("Errored while writing to string");
+        string
+    }
+}
+
+impl<Kind: ErrorKind + Clone> DynError for CustomError<'_, Kind> {
+    fn suggestions(&self) -> Vec<&str> {
+        self.suggestions.iter().map(AsRef::as_ref).collect()
+    }
+
+    fn notes(&self) -> Vec<&str> {
+        self.notes.iter().map(AsRef::as_ref).collect()
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn underlying_errors(&self) -> Vec<&dyn DynError> {
+        self.underlying_errors
+            .iter()
+            .map(|e| e as &dyn DynError)
+            .collect()
+    }
+
+    fn display_html(&self, mut f: &mut dyn fmt::Write, allow_trim_context: bool) -> fmt::Result {
+        FullErrorContent::display_html(self, &mut f, None, allow_trim_context)
+    }
+}
+
+impl<Kind: ErrorKind + Clone> DynError for BoxedError<'_, Kind> {
+    fn suggestions(&self) -> Vec<&str> {
+        self.content.suggestions()
+    }
+
+    fn notes(&self) -> Vec<&str> {
+        self.content.notes()
+    }
+
+    fn version(&self) -> &str {
+        self.content.version()
+    }
+
+    fn underlying_errors(&self) -> Vec<&dyn DynError> {
+        self.content.underlying_errors()
+    }
+
+    fn display_html(&self, f: &mut dyn fmt::Write, allow_trim_context: bool) -> fmt::Result {
+        DynError::display_html(self.content.as_ref(), f, allow_trim_context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicKind, Context, CreateError};
+
+    #[test]
+    fn underlying() {
+        let error: Box<dyn DynError> = Box::new(
+            BoxedError::new(BasicKind::Error, "Outer", "outer", Context::default())
+                .suggestions(["a", "b"])
+                .note("c")
+                .version("v1")
+                .add_underlying_error(CustomError::new(
+                    BasicKind::Warning,
+                    "Inner",
+                    "inner",
+                    Context::default(),
+                )),
+        );
+        assert_eq!(error.suggestions(), ["a", "b"]);
+        assert_eq!(error.notes(), ["c"]);
+        assert_eq!(error.version(), "v1");
+        let underlying = error.underlying_errors();
+        assert_eq!(underlying.len(), 1);
+        assert_eq!(underlying[0].title(), "Inner");
+        assert_eq!(underlying[0].kind_descriptor(), "warning");
+        assert!(error
+            .to_html(true)
+            .contains("<p class='ce-title'>Inner</p>"));
+    }
+}
@@ -0,0 +1,245 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{CustomError, ErrorKind, FullErrorContent, SerializedError};
+
+/// The version of the schema of [`ErrorEnvelope`] written by this version of the crate
+pub const ENVELOPE_SCHEMA_VERSION: u32 = 1;
+
+/// A batch of errors to transfer between processes, for example from a worker that parses the
+/// input to a UI process that renders the errors. The errors are serialized in the versioned
+/// [`SerializedError`] format as JSON, and the texts of the sources can be included so the
+/// receiving side can show the contexts without access to the files (see
+/// [`Self::sources`]). The envelope itself implements serde so it can be sent with any RPC
+/// framework, or as JSON with [`Self::to_bytes`] and [`Self::from_bytes`]. With the `zstd`
+/// feature the sources are compressed.
+/// ```
+/// use context_error::*;
+/// // In the worker
+/// let text = "id,value\n1,80o0";
+/// let error = CustomError::new(BasicKind::Error, "Invalid number", "", Context::default().source("data.csv").byte_range(11..15));
+/// let bytes = ErrorEnvelope::new([&error]).unwrap().add_source("data.csv", text).to_bytes();
+/// // In the UI process
+/// let envelope = ErrorEnvelope::from_bytes(&bytes).unwrap();
+/// let errors: Vec<CustomError<BasicKind>> = envelope.errors().unwrap();
+/// DisplayConfig::default().unicode(false).source_resolver(envelope.sources().unwrap()).set_thread_local();
+/// assert_eq!(
+///     errors[0].to_string(),
+///     "error: Invalid number\n  +-[data.csv:2:3-6][B:11-15]\n2 | 1,80o0\n  *   ----\n  '\n\n"
+/// );
+/// # DisplayConfig::reset_thread_local();
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ErrorEnvelope {
+    /// The version of the schema of this envelope, see [`ENVELOPE_SCHEMA_VERSION`]
+    pub schema_version: u32,
+    /// The errors as a JSON array of [`SerializedError`]s
+    pub errors: Vec<u8>,
+    /// The texts of the sources the errors refer to
+    #[serde(default)]
+    pub sources: Vec<EnvelopeSource>,
+}
+
+/// The text of a source in an [`ErrorEnvelope`]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EnvelopeSource {
+    /// The name of the source, as used in the contexts
+    pub name: String,
+    /// The compression of the data
+    #[serde(default)]
+    pub compression: Compression,
+    /// The (compressed) UTF-8 text of the source
+    pub data: Vec<u8>,
+}
+
+/// The compression of the sources in an [`ErrorEnvelope`]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub enum Compression {
+    /// Not compressed
+    #[default]
+    None,
+    /// Compressed with zstd, only available with the `zstd` feature
+    Zstd,
+}
+
+/// An error while writing or reading an [`ErrorEnvelope`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnvelopeError(String);
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid error envelope: {}", self.0)
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+impl ErrorEnvelope {
+    /// Serialize the given errors into an envelope, without any sources
+    /// # Errors
+    /// If the kind of any error cannot be serialized.
+    pub fn new<
+        'a,
+        'text: 'a,
+        Kind: ErrorKind + Serialize + 'a,
+        E: FullErrorContent<'text, Kind> + 'a,
+    >(
+        errors: impl IntoIterator<Item = &'a E>,
+    ) -> Result<Self, EnvelopeError> {
+        let errors: Vec<SerializedError<Kind>> = errors
+            .into_iter()
+            .map(|error| SerializedError::V1(crate::SerializedErrorV1::from_error(error)))
+            .collect();
+        Ok(Self {
+            schema_version: ENVELOPE_SCHEMA_VERSION,
+            errors: serde_json::to_vec(&errors).map_err(|e| EnvelopeError(e.to_string()))?,
+            sources: Vec::new(),
+        })
+    }
+
+    /// Add the text of a source, compressed with zstd if the `zstd` feature is enabled
+    #[must_use]
+    pub fn add_source(mut self, name: impl Into<String>, text: &str) -> Self {
+        #[cfg(feature = "zstd")]
+        let (compression, data) = zstd::encode_all(text.as_bytes(), 0).map_or_else(
+            |_| (Compression::None, text.as_bytes().to_vec()),
+            |data| (Compression::Zstd, data),
+        );
+        #[cfg(not(feature = "zstd"))]
+        let (compression, data) = (Compression::None, text.as_bytes().to_vec());
+        self.sources.push(EnvelopeSource {
+            name: name.into(),
+            compression,
+            data,
+        });
+        self
+    }
+
+    /// Deserialize the errors
+    /// # Errors
+    /// If the envelope was written with a newer schema version or the errors are invalid.
+    pub fn errors<Kind: DeserializeOwned>(
+        &self,
+    ) -> Result<Vec<CustomError<'static, Kind>>, EnvelopeError> {
+        self.check_version()?;
+        let errors: Vec<SerializedError<Kind>> =
+            serde_json::from_slice(&self.errors).map_err(|e| EnvelopeError(e.to_string()))?;
+        Ok(errors.into_iter().map(CustomError::from).collect())
+    }
+
+    /// Get the texts of the sources by their name, which can be used as
+    /// [`crate::SourceResolver`] to show the contexts of the errors
+    /// # Errors
+    /// If the envelope was written with a newer schema version, a source is compressed with zstd
+    /// while the `zstd` feature is not enabled, or a source is not valid (compressed) UTF-8.
+    pub fn sources(&self) -> Result<BTreeMap<String, String>, EnvelopeError> {
+        self.check_version()?;
+        self.sources
+            .iter()
+            .map(|source| {
+                let data = match source.compression {
+                    Compression::None => source.data.clone(),
+                    #[cfg(feature = "zstd")]
+                    Compression::Zstd => zstd::decode_all(source.data.as_slice())
+                        .map_err(|e| EnvelopeError(format!("{}: {e}", source.name)))?,
+                    #[cfg(not(feature = "zstd"))]
+                    Compression::Zstd => {
+                        return Err(EnvelopeError(format!(
+                            "{} is compressed with zstd, which needs the `zstd` feature",
+                            source.name
+                        )))
+                    }
+                };
+                String::from_utf8(data)
+                    .map(|text| (source.name.clone(), text))
+                    .map_err(|e| EnvelopeError(format!("{}: {e}", source.name)))
+            })
+            .collect()
+    }
+
+    /// Serialize this envelope as JSON
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("An envelope can always be serialized")
+    }
+
+    /// Deserialize an envelope from JSON, see [`Self::to_bytes`]
+    /// # Errors
+    /// If the bytes are not a valid envelope.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EnvelopeError> {
+        serde_json::from_slice(bytes).map_err(|e| EnvelopeError(e.to_string()))
+    }
+
+    /// Check that this envelope can be read by this version of the crate
+    fn check_version(&self) -> Result<(), EnvelopeError> {
+        if self.schema_version > ENVELOPE_SCHEMA_VERSION {
+            Err(EnvelopeError(format!(
+                "unsupported schema version {}, expected at most {ENVELOPE_SCHEMA_VERSION}",
+                self.schema_version
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicKind, Context, CreateError};
+
+    #[test]
+    fn round_trip() {
+        let errors = [
+            CustomError::new(
+                BasicKind::Error,
+                "Invalid number",
+                "",
+                Context::default().source("a.csv").line_index(1),
+            ),
+            CustomError::message(BasicKind::Warning, "Empty file")
+                .add_underlying_error(CustomError::message(BasicKind::Error, "Could not read")),
+        ];
+        let text = "id,value\n".repeat(100);
+        let envelope = ErrorEnvelope::new(&errors)
+            .unwrap()
+            .add_source("a.csv", &text);
+        #[cfg(feature = "zstd")]
+        {
+            assert_eq!(envelope.sources[0].compression, Compression::Zstd);
+            assert!(envelope.sources[0].data.len() < text.len());
+        }
+        let read = ErrorEnvelope::from_bytes(&envelope.to_bytes()).unwrap();
+        assert_eq!(read, envelope);
+        let read_errors: Vec<CustomError<BasicKind>> = read.errors().unwrap();
+        assert_eq!(read_errors.len(), 2);
+        assert_eq!(read_errors[1], errors[1]);
+        assert_eq!(
+            read_errors[0].get_contexts()[0].location_string(),
+            "a.csv:2"
+        );
+        assert_eq!(read.sources().unwrap()["a.csv"], text);
+    }
+
+    #[test]
+    fn invalid() {
+        let envelope = ErrorEnvelope {
+            schema_version: ENVELOPE_SCHEMA_VERSION + 1,
+            ..ErrorEnvelope::new::<BasicKind, CustomError<BasicKind>>([]).unwrap()
+        };
+        assert!(envelope.errors::<BasicKind>().is_err());
+        assert!(envelope.sources().is_err());
+        assert!(ErrorEnvelope::from_bytes(b"[]").is_err());
+        let compressed = ErrorEnvelope {
+            sources: vec![EnvelopeSource {
+                name: "a.csv".to_string(),
+                compression: Compression::Zstd,
+                data: vec![1, 2, 3],
+            }],
+            ..ErrorEnvelope::new::<BasicKind, CustomError<BasicKind>>([]).unwrap()
+        };
+        assert!(compressed.sources().is_err());
+    }
+}
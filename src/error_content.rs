@@ -1,6 +1,57 @@
 use std::borrow::Cow;
 
-use crate::{Coloured, Context, ErrorKind};
+use crate::{html_inline, Coloured, Context, ErrorKind, Inline, MergeSettings, Severity};
+
+/// Display the title of an error, the descriptor and code of the kind followed by the short
+/// description, without trailing newline. The descriptor is red if the severity of the error is
+/// [`Severity::Error`] or unknown.
+pub(crate) fn display_title<Kind: ErrorKind>(
+    f: &mut std::fmt::Formatter<'_>,
+    kind: &Kind,
+    severity: Option<Severity>,
+    title: &str,
+) -> std::fmt::Result {
+    let descriptor = kind.code().map_or_else(
+        || kind.descriptor().to_string(),
+        |code| format!("{}[{code}]", kind.descriptor()),
+    );
+    write!(
+        f,
+        "{}: {title}",
+        if let Some(colour) = kind.color() {
+            descriptor.styled(colour.into())
+        } else if severity.map_or(true, |severity| severity == Severity::Error) {
+            descriptor.red()
+        } else {
+            descriptor.blue()
+        },
+    )
+}
+
+/// The highest severity of the kind and the effective severities of the underlying errors, see
+/// [`FullErrorContent::effective_severity`]
+fn effective_severity<'text, Kind: ErrorKind, E: FullErrorContent<'text, Kind>>(
+    kind: &Kind,
+    underlying_errors: &[E],
+    settings: Kind::Settings,
+) -> Severity {
+    let severity = kind.severity(settings.clone());
+    underlying_errors
+        .iter()
+        .map(|error| error.effective_severity(settings.clone()))
+        .fold(severity, Ord::max)
+}
+
+/// The versions of an error about an unsupported version, see
+/// [`crate::CreateError::version_mismatch`] and [`StaticErrorContent::get_version_mismatch`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct VersionMismatch {
+    /// The expected version
+    pub expected: String,
+    /// The version that was found
+    pub found: String,
+}
 
 /// A structure that contains basic error content
 pub trait StaticErrorContent<'text>
@@ -19,12 +70,60 @@ where
     /// The version
     fn get_version(&self) -> Cow<'text, str>;
 
+    /// The short description borrowed from this error where possible, to avoid the clone of
+    /// [`Self::get_short_description`] when it is only read, as is done while displaying. The
+    /// default implementation uses [`Self::get_short_description`].
+    fn short_description_ref<'a>(&'a self) -> Cow<'a, str>
+    where
+        'text: 'a,
+    {
+        self.get_short_description()
+    }
+
+    /// The long description borrowed from this error where possible, see
+    /// [`Self::short_description_ref`]
+    fn long_description_ref<'a>(&'a self) -> Cow<'a, str>
+    where
+        'text: 'a,
+    {
+        self.get_long_description()
+    }
+
+    /// The version borrowed from this error where possible, see [`Self::short_description_ref`]
+    fn version_ref<'a>(&'a self) -> Cow<'a, str>
+    where
+        'text: 'a,
+    {
+        self.get_version()
+    }
+
+    /// The notes, short remarks shown below the description that are not errors themselves
+    fn get_notes<'a>(&'a self) -> Cow<'a, [Cow<'text, str>]> {
+        Cow::Borrowed(&[])
+    }
+
+    /// The expected and found version of an error created with
+    /// [`crate::CreateError::version_mismatch`] or with an expected version set with
+    /// [`crate::CreateError::expected_version`], if this is such an error
+    fn get_version_mismatch(&self) -> Option<VersionMismatch> {
+        None
+    }
+
     /// Check if these two can be merged
     fn could_merge(&self, other: &Self) -> bool {
-        self.get_short_description() == other.get_short_description()
-            && self.get_long_description() == other.get_long_description()
-            && self.get_suggestions() == other.get_suggestions()
-            && self.get_version() == other.get_version()
+        StaticErrorContent::could_merge_with(self, other, MergeSettings::default())
+    }
+
+    /// Check if these two can be merged, with the given settings to loosen the requirements
+    fn could_merge_with(&self, other: &Self, settings: MergeSettings) -> bool {
+        settings.normalise(&self.short_description_ref())
+            == settings.normalise(&other.short_description_ref())
+            && settings.normalise(&self.long_description_ref())
+                == settings.normalise(&other.long_description_ref())
+            && (settings.ignore_suggestions
+                || same_suggestions(&self.get_suggestions(), &other.get_suggestions(), settings))
+            && (settings.ignore_version || self.version_ref() == other.version_ref())
+            && self.get_notes() == other.get_notes()
     }
 
     /// Display this error nicely (used for debug and normal display).
@@ -38,61 +137,113 @@ where
         underlying_errors: &[UnderlyingError],
         allow_trim_context: bool,
     ) -> std::fmt::Result {
-        writeln!(
-            f,
-            "{}: {}",
-            if settings
-                .clone()
-                .map_or(true, |settings| kind.is_error(settings))
-            {
-                kind.descriptor().red()
-            } else {
-                kind.descriptor().blue()
-            },
-            self.get_short_description(),
-        )?;
-        let last = contexts.len().saturating_sub(1);
+        // Align the gutters of this error and all its underlying errors
+        let current = crate::DisplayConfig::current();
         let margin = contexts
             .iter()
-            .map(|c| c.margin())
+            .map(Context::margin)
             .max()
-            .unwrap_or_default();
-        let mut first = true;
-        for (index, context) in contexts.iter().enumerate() {
-            if !context.is_empty() {
-                let merged = match (first, index == last) {
-                    (true, true) => crate::Merged::No,
-                    (true, false) => crate::Merged::First(margin),
-                    (false, false) => crate::Merged::Middle(margin),
-                    (false, true) => crate::Merged::Last(margin),
-                };
-                context.display(f, None, merged, allow_trim_context)?;
-                if merged.trailing_decoration() {
-                    writeln!(f)?
-                };
-                first = false;
-            }
+            .unwrap_or_default()
+            .max(crate::max_margin(underlying_errors));
+        if !underlying_errors.is_empty() && margin > current.gutter_width {
+            return current.gutter_width(margin).scoped(|| {
+                self.display_with_context(
+                    f,
+                    kind,
+                    settings,
+                    contexts,
+                    underlying_errors,
+                    allow_trim_context,
+                )
+            });
         }
-        writeln!(f, "{}", self.get_long_description())?;
-        match self.get_suggestions().len() {
-            0 => Ok(()),
-            1 => writeln!(
+        let severity = settings
+            .clone()
+            .map(|settings| effective_severity(&kind, underlying_errors, settings));
+        display_title(f, &kind, severity, &self.short_description_ref())?;
+        writeln!(f)?;
+        let config = crate::DisplayConfig {
+            highlight_colour: kind.color(),
+            ..current
+        };
+        if config.verbosity == crate::Verbosity::Short {
+            return writeln!(f, "{}", Inline(&self.long_description_ref()));
+        }
+        let allow_trim_context =
+            allow_trim_context && config.verbosity != crate::Verbosity::Verbose;
+        if contexts.iter().any(|c| c.is_shown(&config)) {
+            let summary = occurrence_summary(contexts, &config);
+            crate::display_context_group(
                 f,
-                "{}: {}?",
-                "Did you mean".blue(),
-                self.get_suggestions()[0]
-            ),
-            _ => writeln!(
+                contexts,
+                summary.as_deref().map(crate::Trailer::Summary),
+                allow_trim_context,
+                &config,
+            )?;
+            writeln!(f)?;
+        }
+        writeln!(f, "{}", Inline(&self.long_description_ref()))?;
+        for note in self.get_notes().iter() {
+            writeln!(f, "{}: {}", "note".blue(), Inline(note))?;
+        }
+        let suggestions = self.get_suggestions();
+        let shown = suggestions.len().min(config.max_suggestions);
+        let more = suggestions.len() - shown;
+        let line = match suggestions.len() {
+            0 => None,
+            1 => Some(("Did you mean", suggestions[0].to_string())),
+            _ => Some((
+                "Did you mean any of",
+                format!(
+                    "{}{}",
+                    suggestions[..shown].join(", "),
+                    if more > 0 {
+                        format!(", and {more} more")
+                    } else {
+                        String::new()
+                    }
+                ),
+            )),
+        };
+        if let Some((label, line)) = line {
+            if suggestions.len() > config.suggestion_list
+                || crate::text_width(label) + crate::text_width(&line) + 3 > config.width
+            {
+                writeln!(f, "{}:", label.blue())?;
+                let bullet = config.symbols().bullet;
+                for suggestion in &suggestions[..shown] {
+                    writeln!(f, "  {bullet} {suggestion}")?;
+                }
+                if more > 0 {
+                    writeln!(f, "  {bullet} {}", format!("and {more} more").dimmed())?;
+                }
+            } else {
+                writeln!(f, "{}: {line}?", label.blue())?;
+            }
+        }
+        if !self.version_ref().is_empty()
+            && config.hoisted_version.as_deref() != Some(&self.version_ref())
+        {
+            writeln!(f, "{}: {}", "Version".green(), self.version_ref())?;
+        }
+        if !underlying_errors.is_empty()
+            && config.max_depth == 0
+            && config.verbosity != crate::Verbosity::Verbose
+        {
+            let levels = underlying_depth(underlying_errors);
+            return writeln!(
                 f,
-                "{}: {}?",
-                "Did you mean any of".blue(),
-                self.get_suggestions().join(", ")
-            ),
-        }?;
-        if !self.get_version().is_empty() {
-            writeln!(f, "{}: {}", "Version".green(), self.get_version())?;
+                "{}",
+                format!(
+                    "{} {levels} more level{} of underlying errors",
+                    config.symbols().ellipsis,
+                    if levels == 1 { "" } else { "s" }
+                )
+                .dimmed()
+            );
         }
-        match underlying_errors.len() {
+        let nested = config.clone().max_depth(config.max_depth.saturating_sub(1));
+        nested.scoped(|| match underlying_errors.len() {
             0 => Ok(()),
             1 => {
                 writeln!(f, "{}:", "Underlying error".yellow(),)?;
@@ -110,7 +261,7 @@ where
                 }
                 Ok(())
             }
-        }
+        })
     }
 
     fn display_html_with_context<
@@ -125,21 +276,70 @@ where
         underlying_errors: &[UnderlyingError],
         allow_trim_context: bool,
     ) -> std::fmt::Result {
-        write!(f, "<div class='{}'>", kind.descriptor(),)?;
+        let html = crate::DisplayConfig::with_current(|config| config.html.clone());
+        write!(
+            f,
+            "<div class='{} {} {}'{}>",
+            html.class("error"),
+            html.class(&format!("kind-{}", kind.descriptor())),
+            html.class(
+                if settings.clone().map_or(true, |settings| {
+                    effective_severity(&kind, underlying_errors, settings) == Severity::Error
+                }) {
+                    "severity-error"
+                } else {
+                    "severity-warning"
+                }
+            ),
+            if html.correlation_ids {
+                format!(
+                    " data-correlation-id='{:016x}'",
+                    correlation_id(&kind, &self.short_description_ref(), contexts)
+                )
+            } else {
+                String::new()
+            }
+        )?;
 
-        write!(f, "<p class='title'>")?;
-        html_escape(f, &self.get_short_description())?;
+        write!(f, "<p class='{}'>", html.class("title"))?;
+        html_escape(f, &self.short_description_ref())?;
         write!(f, "</p>")?;
 
-        write!(f, "<div class='contexts'>")?;
-        for context in contexts.iter() {
-            context.display_html(f, allow_trim_context)?;
+        write!(f, "<div class='{}'>", html.class("contexts"))?;
+        // Give the contexts ids if any highlight links to another context
+        let prefix = contexts
+            .iter()
+            .any(|c| c.highlights.iter().any(|h| h.link.is_some()))
+            .then(|| {
+                let mut hash = Fnv::default();
+                hash.write(&self.short_description_ref());
+                for context in contexts {
+                    hash.write(&context.location_string());
+                    hash.write(context.get_lines());
+                }
+                format!("{}-{:016x}", html.class("context"), hash.0)
+            });
+        for (index, context) in contexts.iter().enumerate() {
+            context.display_html(
+                f,
+                allow_trim_context,
+                prefix.as_deref().map(|prefix| crate::HtmlAnchor {
+                    prefix,
+                    index,
+                    contexts: contexts.len(),
+                }),
+            )?;
         }
         write!(f, "</div>")?;
 
-        write!(f, "<p class='description'>")?;
-        html_escape(f, &self.get_long_description())?;
+        write!(f, "<p class='{}'>", html.class("description"))?;
+        html_inline(f, &self.long_description_ref())?;
         write!(f, "</p>")?;
+        for note in self.get_notes().iter() {
+            write!(f, "<p class='{}'>note: ", html.class("note"))?;
+            html_inline(f, note)?;
+            write!(f, "</p>")?;
+        }
         if !self.get_suggestions().is_empty() {
             write!(
                 f,
@@ -150,16 +350,31 @@ where
                     " any of"
                 }
             )?;
-            for suggestion in self.get_suggestions().iter() {
-                write!(f, "<li class='suggestion'>")?;
+            let max = crate::DisplayConfig::with_current(|config| config.max_suggestions);
+            let suggestions = self.get_suggestions();
+            for suggestion in suggestions.iter().take(max) {
+                write!(f, "<li class='{}'>", html.class("suggestion"))?;
                 html_escape(f, suggestion)?;
                 write!(f, "</li>")?;
             }
+            if suggestions.len() > max {
+                write!(
+                    f,
+                    "<li class='{}'>and {} more</li>",
+                    html.class("more"),
+                    suggestions.len() - max
+                )?;
+            }
             write!(f, "</ul>")?;
         }
-        if !self.get_version().is_empty() {
-            write!(f, "<p class='version'>Version: <span class='version-text'>")?;
-            html_escape(f, &self.get_version())?;
+        if !self.version_ref().is_empty() {
+            write!(
+                f,
+                "<p class='{}'>Version: <span class='{}'>",
+                html.class("version"),
+                html.class("version-text")
+            )?;
+            html_escape(f, &self.version_ref())?;
             write!(f, "</span></p>")?;
         }
         if !underlying_errors.is_empty() {
@@ -173,7 +388,7 @@ where
                 }
             )?;
             for error in underlying_errors.iter() {
-                write!(f, "<li class='underlying_error'>")?;
+                write!(f, "<li class='{}'>", html.class("underlying-error"))?;
                 error.display_html(f, settings.clone(), allow_trim_context)?;
                 write!(f, "</li>")?;
             }
@@ -202,9 +417,14 @@ where
 
     /// Check if these two can be merged
     fn could_merge(&self, other: &Self) -> bool {
+        FullErrorContent::could_merge_with(self, other, MergeSettings::default())
+    }
+
+    /// Check if these two can be merged, with the given settings to loosen the requirements
+    fn could_merge_with(&self, other: &Self, settings: MergeSettings) -> bool {
         self.get_kind() == other.get_kind()
             && self.get_underlying_errors() == other.get_underlying_errors()
-            && StaticErrorContent::could_merge(self, other)
+            && StaticErrorContent::could_merge_with(self, other, settings)
     }
 
     /// Display this error nicely in text
@@ -241,6 +461,16 @@ where
         )
     }
 
+    /// Display this error with all underlying errors, regardless of the maximal depth set with
+    /// [`crate::DisplayConfig::max_depth`]
+    fn to_string_full_depth(&self) -> String {
+        crate::DisplayConfig::current()
+            .max_depth(usize::MAX)
+            .scoped(|| {
+                DisplayFn(|f: &mut std::fmt::Formatter<'_>| self.display(f, None, true)).to_string()
+            })
+    }
+
     /// Display this error nicely in HTML as a convenience method (similar to `to_string` which is automatically made if you support `Display`)
     fn to_html(&self, allow_trim_context: bool) -> String {
         let mut string = String::new();
@@ -249,6 +479,214 @@ where
         string
     }
 
+    /// Write the HTML of this error directly to an IO writer, without building it in memory
+    /// first (see [`crate::IoWriter`] and [`crate::write_html_stream`] for large reports).
+    /// # Errors
+    /// If writing to the writer fails.
+    fn write_html(
+        &self,
+        writer: impl std::io::Write,
+        settings: Option<<Kind as ErrorKind>::Settings>,
+        allow_trim_context: bool,
+    ) -> std::io::Result<()> {
+        let mut writer = crate::IoWriter::new(writer);
+        let result = self.display_html(&mut writer, settings, allow_trim_context);
+        writer.finish(result)?;
+        writer.into_inner().flush()
+    }
+
+    /// Display this error as a standalone SVG image (monospace text with colours), for embedding
+    /// in documentation or bug reports where neither a terminal nor CSS is available.
+    fn to_svg(&self, allow_trim_context: bool) -> String {
+        let (text, runs) = crate::record_styles(&DisplayFn(|f: &mut std::fmt::Formatter<'_>| {
+            self.display(f, None, allow_trim_context)
+        }));
+        crate::styled_to_svg(&text, &runs)
+    }
+
+    /// Display this error as a small card of at most four lines without colours, for status
+    /// bars, tooltips, or chat messages where the full rendering is too tall. The card shows the
+    /// title, the line of the primary highlight of the first context with a highlight underlined
+    /// with its comment, and the location of that context. Long lines are cropped around the
+    /// highlight to fit in the width set with [`crate::DisplayConfig::width`].
+    /// ```
+    /// use context_error::*;
+    /// let error = CustomError::new(
+    ///     BasicKind::Error,
+    ///     "Invalid number",
+    ///     "This column is not a number",
+    ///     Context::default().source("data.csv").line_index(8).lines(0, "  null,80o0").add_highlight((0, 7..11, "not a number")),
+    /// );
+    /// assert_eq!(error.to_compact(), "\
+    /// error: Invalid number
+    ///   null,80o0
+    ///        ^^^^ not a number
+    ///   at data.csv:9:8-11");
+    /// ```
+    fn to_compact(&self) -> String {
+        let kind = self.get_kind();
+        let mut card = format!(
+            "{}: {}",
+            kind.code().map_or_else(
+                || kind.descriptor().to_string(),
+                |code| format!("{}[{code}]", kind.descriptor()),
+            ),
+            self.short_description_ref()
+        );
+        let contexts = self.get_contexts();
+        let Some(context) = contexts
+            .iter()
+            .find(|c| c.primary_highlight().is_some())
+            .or_else(|| contexts.iter().find(|c| !c.is_empty()))
+        else {
+            return card;
+        };
+        if let Some((highlight, line)) = context.primary_highlight().and_then(|h| {
+            context.get_lines().lines().nth(h.line).map(|line| {
+                let line = crate::DisplayConfig::with_current(|c| c.transform_text(line))
+                    .unwrap_or_else(|| line.to_string());
+                (h, line.replace('\t', " "))
+            })
+        }) {
+            let width = crate::DisplayConfig::with_current(|config| config.width).max(20) - 2;
+            let chars: Vec<char> = line.trim_end().chars().collect();
+            let indent = chars.iter().take_while(|c| c.is_whitespace()).count();
+            let end = highlight
+                .offset
+                .saturating_add(highlight.length.max(1))
+                .min(chars.len());
+            let mut start = indent.min(highlight.offset);
+            if end.saturating_sub(start) > width {
+                start = start.max(highlight.offset.saturating_sub(width / 4));
+            }
+            let shown: String = chars.iter().skip(start).take(width).collect();
+            let column = highlight.offset - start;
+            let underline = end
+                .saturating_sub(highlight.offset)
+                .min(width.saturating_sub(column))
+                .max(1);
+            card.push_str(&format!(
+                "\n  {shown}\n  {}{}",
+                " ".repeat(column),
+                "^".repeat(underline)
+            ));
+            if let Some(comment) = &highlight.comment {
+                card.push_str(&format!(" {comment}"));
+            }
+        }
+        let location = context.location_string();
+        if !location.is_empty() {
+            card.push_str(&format!("\n  at {location}"));
+        }
+        card
+    }
+
+    /// Convert this error into an OpenTelemetry log event, with the severity mapped to the
+    /// OpenTelemetry severity (`ERROR`, `WARN`, or `DEBUG` for ignored errors, see
+    /// [`Self::effective_severity`]) and the location, kind, and fingerprint as attributes (see
+    /// [`crate::OtelEvent`]). Without settings the severity is `ERROR`.
+    /// ```
+    /// use context_error::*;
+    /// let error = CustomError::new(
+    ///     BasicKind::Warning,
+    ///     "Invalid number",
+    ///     "",
+    ///     Context::default().source("data.csv").line_index(8).lines(0, "null,80o0").add_highlight((0, 5..9)),
+    /// );
+    /// let event = error.to_otel_event(Some(()));
+    /// assert_eq!(event.severity, opentelemetry::logs::Severity::Warn);
+    /// assert_eq!(event.body, "Invalid number");
+    /// assert!(event.attributes.iter().any(|(key, value)| key.as_str() == "code.line.number"
+    ///     && *value == opentelemetry::logs::AnyValue::from(9_i64)));
+    /// ```
+    #[cfg(feature = "otel")]
+    fn to_otel_event(&self, settings: Option<Kind::Settings>) -> crate::OtelEvent {
+        crate::OtelEvent::from_error(self, settings)
+    }
+
+    /// The severity of this error taking its underlying errors into account, the highest of the
+    /// severity of its kind (see [`ErrorKind::severity`]) and the effective severities of all its
+    /// underlying errors. This way a warning that wraps an error does not hide the severity of its
+    /// cause. This is used for the colour of the title and by [`crate::Report`].
+    /// ```
+    /// use context_error::*;
+    /// let error = CustomError::message(BasicKind::Warning, "Could not load the config")
+    ///     .add_underlying_error(CustomError::message(BasicKind::Error, "Missing file"));
+    /// assert_eq!(error.get_kind().severity(()), Severity::Warning);
+    /// assert_eq!(error.effective_severity(()), Severity::Error);
+    /// ```
+    fn effective_severity(&self, settings: Kind::Settings) -> Severity {
+        effective_severity(&self.get_kind(), &self.get_underlying_errors(), settings)
+    }
+
+    /// Compare errors for presentation in a report: the most severe errors first (see
+    /// [`Self::effective_severity`]), then by the location of their first context (see
+    /// [`Context::cmp_location`]), and finally by their short description. Errors without any
+    /// context are placed after the errors of the same severity with a context. See
+    /// [`crate::Report::sort`].
+    /// ```
+    /// use context_error::*;
+    /// let error = |kind, line| BoxedError::new(kind, "Invalid", "", Context::default().source("a.csv").line_index(line));
+    /// let mut errors = vec![error(BasicKind::Warning, 1), error(BasicKind::Error, 5), error(BasicKind::Error, 2)];
+    /// errors.sort_by(|a, b| a.cmp_for_report(b, ()));
+    /// assert_eq!(
+    ///     errors.iter().map(|e| e.get_contexts()[0].location_string()).collect::<Vec<_>>(),
+    ///     ["a.csv:3", "a.csv:6", "a.csv:2"]
+    /// );
+    /// ```
+    fn cmp_for_report(&self, other: &Self, settings: Kind::Settings) -> std::cmp::Ordering {
+        other
+            .effective_severity(settings.clone())
+            .cmp(&self.effective_severity(settings))
+            .then_with(|| {
+                crate::combine::cmp_first_location(&self.get_contexts(), &other.get_contexts())
+            })
+            .then_with(|| {
+                self.short_description_ref()
+                    .cmp(&other.short_description_ref())
+            })
+    }
+
+    /// A fingerprint that identifies this error across runs of a program, for example to
+    /// compare the errors of two runs (see [`crate::diff_reports`]). It is built from the kind,
+    /// short description, sources, and highlighted texts, but not the line numbers, so that an
+    /// error keeps its fingerprint if unrelated lines are added above it. It is stable across
+    /// platforms and versions of Rust.
+    fn fingerprint(&self) -> u64 {
+        fingerprint(
+            &self.get_kind(),
+            &self.short_description_ref(),
+            &self.get_contexts(),
+        )
+        .0
+    }
+
+    /// An id that identifies this error across runs of a program as long as it does not move,
+    /// for example for dashboards that track errors over time. It combines the fingerprint (see
+    /// [`Self::fingerprint`]) with the location of the first context, so unlike the fingerprint it
+    /// changes if the error moves to another line. It is stable across platforms and versions of
+    /// Rust. It can be included in the HTML (see [`crate::HtmlSettings::correlation_ids`]) and
+    /// JSON output (see [`crate::SerializedErrorV1::from_error_with_correlation_id`]).
+    /// ```
+    /// use context_error::*;
+    /// let error = |line| CustomError::new(
+    ///     BasicKind::Error,
+    ///     "Invalid number",
+    ///     "",
+    ///     Context::default().source("data.csv").line_index(line).lines(0, "80o0").add_highlight((0, 0..4)),
+    /// );
+    /// assert_eq!(error(1).correlation_id(), error(1).correlation_id());
+    /// assert_eq!(error(1).fingerprint(), error(2).fingerprint());
+    /// assert_ne!(error(1).correlation_id(), error(2).correlation_id());
+    /// ```
+    fn correlation_id(&self) -> u64 {
+        correlation_id(
+            &self.get_kind(),
+            &self.short_description_ref(),
+            &self.get_contexts(),
+        )
+    }
+
     /// Convert this error into a different error kind. This also converts all underlying errors.
     fn convert<
         NewKind: ErrorKind,
@@ -265,6 +703,10 @@ where
             self.get_short_description(),
             self.get_long_description(),
         );
+        let new = match self.get_version_mismatch() {
+            Some(versions) => new.expected_version(versions.expected),
+            None => new,
+        };
         new.add_contexts(self.get_contexts().iter().cloned())
             .suggestions(self.get_suggestions().iter().cloned())
             .version(self.get_version())
@@ -276,6 +718,106 @@ where
     }
 }
 
+/// The summary of the number of occurrences and files of the given contexts, if there are enough
+/// contexts shown for a summary, see [`crate::DisplayConfig::occurrence_summary`]
+fn occurrence_summary(contexts: &[Context<'_>], config: &crate::DisplayConfig) -> Option<String> {
+    let shown: Vec<_> = contexts.iter().filter(|c| c.is_shown(config)).collect();
+    if shown.len() < config.occurrence_summary.max(1) {
+        return None;
+    }
+    let mut files: Vec<_> = shown.iter().filter_map(|c| c.get_source()).collect();
+    files.sort_unstable();
+    files.dedup();
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    Some(match files.len() {
+        0 => format!("{} occurrence{}", shown.len(), plural(shown.len())),
+        n => format!(
+            "{} occurrence{} in {n} file{}",
+            shown.len(),
+            plural(shown.len()),
+            plural(n)
+        ),
+    })
+}
+
+/// The number of levels of underlying errors below (and including) the given errors
+fn underlying_depth<'text, Kind: ErrorKind, E: FullErrorContent<'text, Kind>>(
+    errors: &[E],
+) -> usize {
+    errors
+        .iter()
+        .map(|e| 1 + underlying_depth(&e.get_underlying_errors()))
+        .max()
+        .unwrap_or_default()
+}
+
+/// A 64 bit FNV-1a hash, which unlike the std hasher is stable across runs and platforms
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    /// Hash the given text, followed by a separator so that consecutive texts cannot collide
+    fn write(&mut self, text: &str) {
+        for byte in text.bytes().chain(std::iter::once(0xff)) {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// The fingerprint of an error with the given properties, see [`FullErrorContent::fingerprint`]
+fn fingerprint<Kind: ErrorKind>(kind: &Kind, short_description: &str, contexts: &[Context]) -> Fnv {
+    let mut hash = Fnv::default();
+    hash.write(kind.descriptor());
+    hash.write(&kind.code().unwrap_or_default());
+    hash.write(short_description);
+    for context in contexts {
+        hash.write(context.get_source().unwrap_or_default());
+        for text in context.highlighted_texts() {
+            hash.write(text.unwrap_or_default());
+        }
+    }
+    hash
+}
+
+/// The correlation id of an error with the given properties, see
+/// [`FullErrorContent::correlation_id`]
+fn correlation_id<Kind: ErrorKind>(
+    kind: &Kind,
+    short_description: &str,
+    contexts: &[Context],
+) -> u64 {
+    let mut hash = fingerprint(kind, short_description, contexts);
+    hash.write(
+        &contexts
+            .iter()
+            .find(|c| !c.is_empty())
+            .map(Context::location_string)
+            .unwrap_or_default(),
+    );
+    hash.0
+}
+
+/// Check if the two lists contain the same suggestions, regardless of order and duplicates
+fn same_suggestions(a: &[Cow<'_, str>], b: &[Cow<'_, str>], settings: MergeSettings) -> bool {
+    let a: Vec<_> = a.iter().map(|s| settings.normalise(s)).collect();
+    let b: Vec<_> = b.iter().map(|s| settings.normalise(s)).collect();
+    a.iter().all(|s| b.contains(s)) && b.iter().all(|s| a.contains(s))
+}
+
+/// Helper to use a rendering function as a [`std::fmt::Display`] implementation
+pub(crate) struct DisplayFn<F>(pub(crate) F);
+
+impl<F: Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result> std::fmt::Display for DisplayFn<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (self.0)(f)
+    }
+}
+
 pub(crate) fn html_escape(
     writer: &mut impl std::fmt::Write,
     text: &str,
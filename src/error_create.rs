@@ -1,14 +1,17 @@
 use std::borrow::Cow;
 
-use crate::{Context, ErrorKind, FullErrorContent, StaticErrorContent};
+use crate::{Context, ErrorKind, ErrorSummary, FullErrorContent, StaticErrorContent};
 
 /// A trait to guarantee identical an API between the boxed and unboxed error version
 pub trait CreateError<'text, Kind>:
-    Sized + Default + PartialEq + FullErrorContent<'text, Kind>
+    Sized + Default + PartialEq + FullErrorContent<'text, Kind> + ErrorSummary
 where
     Kind: ErrorKind,
 {
-    /// Create a new `CustomError`.
+    /// Create a new `CustomError`. This calls the hook set with [`crate::set_on_error_created`].
+    /// With the `time` feature the current time is recorded on the context (if not set before).
+    /// If the limit of an [`crate::ErrorLimiter`] is reached the error is created without the
+    /// context, the hook is still called for these errors.
     ///
     /// ## Arguments
     /// * `kind` - The error kind.
@@ -21,7 +24,118 @@ where
         long_desc: impl Into<Cow<'text, str>>,
         context: Context<'text>,
     ) -> Self {
-        Self::small(kind, short_desc, long_desc).add_context(context)
+        #[cfg(feature = "time")]
+        let context = if context.timestamp.is_none() {
+            context.timestamp(std::time::SystemTime::now())
+        } else {
+            context
+        };
+        let error = Self::small(kind, short_desc, long_desc);
+        if crate::limiter::suppress_error(&error) {
+            crate::error_created(&error);
+            return error;
+        }
+        let error = error.add_context(context);
+        crate::error_created(&error);
+        crate::limiter::count_error(&error);
+        error
+    }
+
+    /// Create a new error with a context for the span of the given item, for example a token or
+    /// node of a parser, see [`crate::Spanned`] and [`Context::of`]
+    fn new_spanned(
+        kind: Kind,
+        short_desc: impl Into<Cow<'text, str>>,
+        long_desc: impl Into<Cow<'text, str>>,
+        spanned: &impl crate::Spanned<'text>,
+    ) -> Self {
+        Self::new(kind, short_desc, long_desc, Context::of(spanned))
+    }
+
+    /// Create a new error without a context or long description, with the message as short
+    /// description. Useful for quick prototyping and for wrapping messages from other libraries.
+    /// ```
+    /// use context_error::*;
+    /// let error = CustomError::message(BasicKind::Warning, "No input files given");
+    /// assert_eq!(error.to_string(), "warning: No input files given\n\n");
+    /// ```
+    fn message(kind: Kind, message: impl Into<Cow<'text, str>>) -> Self {
+        Self::new(kind, message, "", Context::default())
+    }
+
+    /// Create a new error without a context or long description, with the displayed value as
+    /// short description, see [`Self::message`]. Useful to wrap errors from other libraries.
+    /// ```
+    /// use context_error::*;
+    /// let error = "80o0".parse::<u32>().unwrap_err();
+    /// let error = CustomError::from_display(BasicKind::Error, &error);
+    /// assert_eq!(error.to_string(), "error: invalid digit found in string\n\n");
+    /// ```
+    fn from_display(kind: Kind, value: &impl std::fmt::Display) -> Self {
+        Self::message(kind, value.to_string())
+    }
+
+    /// Create a new error about an unsupported version, for example of a file format. The found
+    /// version is set as the version of the error and, if the context has no highlights, the
+    /// first occurrence of the found version in the text of the context is highlighted with the
+    /// expected version as comment. The versions can be retrieved with
+    /// [`StaticErrorContent::get_version_mismatch`].
+    /// ```
+    /// use context_error::*;
+    /// # DisplayConfig::default().unicode(true).set_thread_local();
+    /// let error = CustomError::version_mismatch(
+    ///     BasicKind::Error,
+    ///     "1.x",
+    ///     "2.1",
+    ///     Context::default().source("file.toml").line_index(0).lines(0, "version = \"2.1\""),
+    /// );
+    /// assert_eq!(error.to_string(), "\
+    /// error: Unsupported version
+    ///   ╭─[file.toml:1:12-14]
+    /// 1 │ version = \"2.1\"
+    ///   ╎            ╶─╴expected 1.x
+    ///   ╵
+    /// Expected version 1.x, found version 2.1
+    /// Version: 2.1
+    /// ");
+    /// let versions = error.get_version_mismatch().unwrap();
+    /// assert_eq!((versions.expected.as_str(), versions.found.as_str()), ("1.x", "2.1"));
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    fn version_mismatch(
+        kind: Kind,
+        expected: impl std::fmt::Display,
+        found: impl Into<Cow<'text, str>>,
+        context: Context<'text>,
+    ) -> Self {
+        let expected = expected.to_string();
+        let found = found.into();
+        let position = context
+            .get_lines()
+            .lines()
+            .enumerate()
+            .filter(|_| !found.is_empty() && context.get_highlights().is_empty())
+            .find_map(|(line, text)| {
+                text.find(found.as_ref())
+                    .map(|byte| (line, text[..byte].chars().count()))
+            });
+        let context = match position {
+            Some((line, offset)) => context.add_highlight((
+                line,
+                offset,
+                found.chars().count(),
+                format!("expected {expected}"),
+            )),
+            None => context,
+        };
+        Self::new(
+            kind,
+            "Unsupported version",
+            format!("Expected version {expected}, found version {found}"),
+            context,
+        )
+        .version(found)
+        .expected_version(expected)
     }
 
     /// Create a new `CustomError`.
@@ -41,15 +155,44 @@ where
     #[must_use]
     fn long_description(self, long_desc: impl Into<Cow<'text, str>>) -> Self;
 
-    /// Extend the suggestions with the given suggestions, does not remove any previously added suggestions
+    /// Extend the suggestions with the given suggestions, does not remove any previously added suggestions.
+    /// Suggestions that are already present are skipped, so the order is by first occurrence.
     #[must_use]
     fn suggestions(self, suggestions: impl IntoIterator<Item = impl Into<Cow<'text, str>>>)
         -> Self;
 
+    /// Add the given notes, will append to the current list. Notes are short remarks shown as
+    /// `note: …` lines below the long description, use underlying errors for remarks that need
+    /// their own kind or context.
+    #[must_use]
+    fn notes(self, notes: impl IntoIterator<Item = impl Into<Cow<'text, str>>>) -> Self;
+
+    /// Add the given note, will append to the current list, see [`Self::notes`].
+    /// ```
+    /// use context_error::*;
+    /// let error = BoxedError::small(BasicKind::Error, "Invalid number", "This is not a number")
+    ///     .note("numbers cannot contain letters")
+    ///     .note("the column was skipped");
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "error: Invalid number\nThis is not a number\nnote: numbers cannot contain letters\nnote: the column was skipped\n"
+    /// );
+    /// ```
+    #[must_use]
+    fn note(self, note: impl Into<Cow<'text, str>>) -> Self {
+        self.notes([note])
+    }
+
     /// Set the version of the underlying format
     #[must_use]
     fn version(self, version: impl Into<Cow<'text, str>>) -> Self;
 
+    /// Set the version of the underlying format that was expected, this marks the error as about
+    /// an unsupported version with the version of the error as the found version, see
+    /// [`StaticErrorContent::get_version_mismatch`]
+    #[must_use]
+    fn expected_version(self, expected: impl Into<Cow<'text, str>>) -> Self;
+
     /// Update with a new context
     #[must_use]
     fn replace_context(self, context: Context<'text>) -> Self;
@@ -78,7 +221,59 @@ where
 
     /// Set the context line index, for every context in this error
     #[must_use]
-    fn overwrite_line_index(self, line_index: u32) -> Self;
+    fn overwrite_line_index(self, line_index: u64) -> Self;
+
+    /// Transform the short description. The description is passed as is, so text that is not
+    /// changed stays borrowed.
+    /// ```
+    /// use std::borrow::Cow;
+    /// use context_error::*;
+    /// let error = CustomError::message(BasicKind::Error, "invalid number")
+    ///     .map_short_description(|d| match d {
+    ///         Cow::Borrowed(d) => Cow::Borrowed(d.strip_prefix("invalid ").unwrap_or(d)),
+    ///         Cow::Owned(d) => Cow::Owned(d.replace("invalid ", "")),
+    ///     });
+    /// assert!(matches!(error.get_short_description(), Cow::Borrowed("number")));
+    /// ```
+    #[must_use]
+    fn map_short_description(self, f: impl FnOnce(Cow<'text, str>) -> Cow<'text, str>) -> Self;
+
+    /// Transform the long description, see [`Self::map_short_description`].
+    /// ```
+    /// use std::borrow::Cow;
+    /// use context_error::*;
+    /// let error = CustomError::small(BasicKind::Error, "Invalid number", "Not a number")
+    ///     .map_long_description(|d| Cow::Owned(format!("{d}, see the manual")));
+    /// assert_eq!(error.get_long_description(), "Not a number, see the manual");
+    /// ```
+    #[must_use]
+    fn map_long_description(self, f: impl FnOnce(Cow<'text, str>) -> Cow<'text, str>) -> Self;
+
+    /// Transform all contexts, for example to add the file name once the error bubbles up to the
+    /// place where the file name is known.
+    /// ```
+    /// use context_error::*;
+    /// let error = BoxedError::new(
+    ///     BasicKind::Error,
+    ///     "Invalid number",
+    ///     "This column is not a number",
+    ///     Context::default().line_index(2).lines(0, "null,80o0,YES"),
+    /// )
+    /// .map_contexts(|c| c.source("file.csv"));
+    /// assert_eq!(error.get_contexts()[0].get_source(), Some("file.csv"));
+    /// ```
+    #[must_use]
+    fn map_contexts(self, f: impl FnMut(Context<'text>) -> Context<'text>) -> Self;
+
+    /// Add the given suggestion only if the condition holds
+    #[must_use]
+    fn with_suggestion_if(self, condition: bool, suggestion: impl Into<Cow<'text, str>>) -> Self {
+        if condition {
+            self.suggestions([suggestion])
+        } else {
+            self
+        }
+    }
 
     /// Create a new error from the given kind
     #[must_use]
@@ -89,9 +284,11 @@ where
         let short_desc = kind.get_short_description();
         let long_desc = kind.get_long_description();
         let suggestions = kind.get_suggestions().to_vec();
+        let notes = kind.get_notes().to_vec();
         let version = kind.get_version();
         Self::small(kind, short_desc, long_desc)
             .suggestions(suggestions)
+            .notes(notes)
             .version(version)
     }
 
@@ -105,13 +302,44 @@ where
         let short_desc = kind.get_short_description();
         let long_desc = kind.get_long_description();
         let suggestions = kind.get_suggestions().to_vec();
+        let notes = kind.get_notes().to_vec();
         let version = kind.get_version();
         let contexts = kind.get_contexts().to_vec();
         let underlying_errors = kind.get_underlying_errors().to_vec();
         Self::small(kind, short_desc, long_desc)
             .suggestions(suggestions)
+            .notes(notes)
             .version(version)
             .add_contexts(contexts)
             .add_underlying_errors(underlying_errors)
     }
 }
+
+/// Helpers to transform the error of a result while it bubbles up, without unpacking the result.
+pub trait ResultExt<'text, T, E, Kind>: Sized
+where
+    E: CreateError<'text, Kind>,
+    Kind: ErrorKind,
+{
+    /// Transform all contexts of the error, if this is an error
+    #[must_use]
+    fn map_err_contexts(self, f: impl FnMut(Context<'text>) -> Context<'text>) -> Self;
+
+    /// Transform the short description of the error, if this is an error
+    #[must_use]
+    fn map_err_short_description(self, f: impl FnOnce(Cow<'text, str>) -> Cow<'text, str>) -> Self;
+}
+
+impl<'text, T, E, Kind> ResultExt<'text, T, E, Kind> for Result<T, E>
+where
+    E: CreateError<'text, Kind>,
+    Kind: ErrorKind,
+{
+    fn map_err_contexts(self, f: impl FnMut(Context<'text>) -> Context<'text>) -> Self {
+        self.map_err(|e| e.map_contexts(f))
+    }
+
+    fn map_err_short_description(self, f: impl FnOnce(Cow<'text, str>) -> Cow<'text, str>) -> Self {
+        self.map_err(|e| e.map_short_description(f))
+    }
+}
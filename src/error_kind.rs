@@ -12,13 +12,78 @@ pub trait ErrorKind: PartialEq + Default {
 
     /// Check if this error can be ignored, meaning fully deleted when combining the errors.
     fn ignored(&self, settings: Self::Settings) -> bool;
+
+    /// The severity of this kind, by default [`Severity::Ignored`] for ignored kinds,
+    /// [`Severity::Error`] for errors, and [`Severity::Warning`] otherwise.
+    fn severity(&self, settings: Self::Settings) -> Severity {
+        if self.ignored(settings.clone()) {
+            Severity::Ignored
+        } else if self.is_error(settings) {
+            Severity::Error
+        } else {
+            Severity::Warning
+        }
+    }
+
+    /// The exit code of a program that stops because of this error, by default 1 for errors and
+    /// 0 otherwise. See [`crate::Report::exit`].
+    fn exit_code(&self, settings: Self::Settings) -> i32 {
+        i32::from(self.is_error(settings))
+    }
+
+    /// A code or name identifying this kind, shown after the descriptor in the title, for example
+    /// `error[UnknownColumn]`. Wrap a kind in [`DisplayKind`] to use its [`std::fmt::Display`]
+    /// implementation.
+    fn code(&self) -> Option<String> {
+        None
+    }
+
+    /// The cause of errors of this kind, used as [`std::error::Error::source`] of the errors.
+    /// Wrap a kind in [`SourceKind`] to use a kind that implements [`std::error::Error`].
+    fn source_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+
+    /// The colour used for the descriptor and the highlights of errors of this kind, by default
+    /// (`None`) the descriptor is red for errors and blue for other kinds and highlights are
+    /// yellow. Colours are only shown if the `colored` feature is enabled.
+    fn color(&self) -> Option<Color> {
+        None
+    }
+}
+
+/// The severity of an [`ErrorKind`], ordered from least to most severe, see
+/// [`ErrorKind::severity`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// A kind that can be ignored
+    Ignored,
+    /// A kind that does not block succeeding the operation
+    Warning,
+    /// A kind that blocks succeeding the operation
+    Error,
+}
+
+/// A colour for rendering an [`ErrorKind`], see [`ErrorKind::color`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum BasicKind {
     #[default]
+    #[cfg_attr(feature = "serde", serde(alias = "error"))]
     Error,
+    #[cfg_attr(feature = "serde", serde(alias = "warning"))]
     Warning,
 }
 
@@ -43,3 +108,92 @@ impl std::fmt::Display for BasicKind {
         write!(f, "{}", self.descriptor())
     }
 }
+
+/// A kind that is shown with its [`std::fmt::Display`] implementation as code in the title of
+/// errors, see [`ErrorKind::code`].
+/// ```
+/// use context_error::*;
+/// #[derive(Clone, Debug, Default, PartialEq)]
+/// struct UnknownColumn;
+/// impl ErrorKind for UnknownColumn {
+///     type Settings = ();
+///     fn descriptor(&self) -> &'static str { "error" }
+///     fn is_error(&self, _settings: ()) -> bool { true }
+///     fn ignored(&self, _settings: ()) -> bool { false }
+/// }
+/// impl std::fmt::Display for UnknownColumn {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "UnknownColumn")
+///     }
+/// }
+/// let error = CustomError::message(DisplayKind(UnknownColumn), "Column 'mz' does not exist");
+/// assert_eq!(error.to_string(), "error[UnknownColumn]: Column 'mz' does not exist\n\n");
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct DisplayKind<Kind>(pub Kind);
+
+impl<Kind: ErrorKind + std::fmt::Display> ErrorKind for DisplayKind<Kind> {
+    type Settings = Kind::Settings;
+    fn descriptor(&self) -> &'static str {
+        self.0.descriptor()
+    }
+    fn is_error(&self, settings: Self::Settings) -> bool {
+        self.0.is_error(settings)
+    }
+    fn ignored(&self, settings: Self::Settings) -> bool {
+        self.0.ignored(settings)
+    }
+    fn severity(&self, settings: Self::Settings) -> Severity {
+        self.0.severity(settings)
+    }
+    fn exit_code(&self, settings: Self::Settings) -> i32 {
+        self.0.exit_code(settings)
+    }
+    fn code(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+    fn source_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source_error()
+    }
+    fn color(&self) -> Option<Color> {
+        self.0.color()
+    }
+}
+
+/// A kind that implements [`std::error::Error`], which is used as the
+/// [`std::error::Error::source`] of errors of this kind (see [`ErrorKind::source_error`]). Like
+/// [`DisplayKind`] it is shown as code in the title of errors.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SourceKind<Kind>(pub Kind);
+
+impl<Kind: ErrorKind + std::error::Error + 'static> ErrorKind for SourceKind<Kind> {
+    type Settings = Kind::Settings;
+    fn descriptor(&self) -> &'static str {
+        self.0.descriptor()
+    }
+    fn is_error(&self, settings: Self::Settings) -> bool {
+        self.0.is_error(settings)
+    }
+    fn ignored(&self, settings: Self::Settings) -> bool {
+        self.0.ignored(settings)
+    }
+    fn severity(&self, settings: Self::Settings) -> Severity {
+        self.0.severity(settings)
+    }
+    fn exit_code(&self, settings: Self::Settings) -> i32 {
+        self.0.exit_code(settings)
+    }
+    fn code(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+    fn source_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+    fn color(&self) -> Option<Color> {
+        self.0.color()
+    }
+}
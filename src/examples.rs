@@ -0,0 +1,249 @@
+//! Deterministic synthetic errors, to benchmark renderers, test UI components that show errors,
+//! and fill style guides with consistent samples. The same arguments always give the same errors.
+//! ```
+//! use context_error::examples;
+//! let errors = examples::generate(4, 2, 42);
+//! assert_eq!(errors.len(), 4);
+//! assert_eq!(errors, examples::generate(4, 2, 42));
+//! for error in &errors {
+//!     println!("{error}");
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::{Context, CreateError, CustomError, ErrorKind, Highlight};
+
+/// The kind of the generated errors, see [`generate`]. Every third kind is a warning, the others
+/// are errors. The index is shown as code, for example `error[E0002]`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ExampleKind(pub usize);
+
+impl ErrorKind for ExampleKind {
+    type Settings = ();
+    fn descriptor(&self) -> &'static str {
+        if self.0 % 3 == 2 {
+            "warning"
+        } else {
+            "error"
+        }
+    }
+    fn is_error(&self, _settings: Self::Settings) -> bool {
+        self.0 % 3 != 2
+    }
+    fn ignored(&self, _settings: Self::Settings) -> bool {
+        false
+    }
+    fn code(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+impl fmt::Display for ExampleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", self.0)
+    }
+}
+
+/// The short and long descriptions of the generated errors
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    ("Invalid number", "This column should contain a number"),
+    ("Unknown column", "This column is not defined in the header"),
+    ("Duplicate key", "Keys have to be unique within a table"),
+    ("Unexpected token", ""),
+    ("Missing value", "Every row needs a value for this column"),
+    (
+        "Unsupported version",
+        "Only versions 1 and 2 of this format are supported",
+    ),
+    ("Unclosed delimiter", ""),
+    (
+        "Invalid escape sequence",
+        "Only `\\n`, `\\t`, and `\\\\` can be escaped",
+    ),
+    (
+        "Value out of range",
+        "The value has to be between 0 and 255",
+    ),
+    (
+        "Unknown identifier",
+        "This name is not defined in this scope",
+    ),
+];
+
+/// The sources of the generated contexts with the lines used as text
+const SOURCES: &[(&str, &[&str])] = &[
+    (
+        "data.csv",
+        &[
+            "id,name,mz,intensity",
+            "17,Angiotensin II,523.7745,8.2e5",
+            "18,Bradykinin,530.788o,1.1e6",
+            "19,,1296.6853,NaN",
+        ],
+    ),
+    (
+        "config.toml",
+        &[
+            "[server]",
+            "port = 80800",
+            "host = \"localhost\"",
+            "timeout = \"30 seconds\"",
+        ],
+    ),
+    (
+        "src/parser.rs",
+        &[
+            "fn parse(text: &str) -> Result<Value, Error> {",
+            "    let (key, value) = text.split_once('=')?;",
+            "    Ok(Value::new(key.trim(), value.parse()?))",
+            "        .map_err(|e| Error::from(e)",
+        ],
+    ),
+];
+
+/// The comments of the generated highlights
+const COMMENTS: &[&str] = &["not a number", "defined here", "expected `,`", "too large"];
+
+/// The suggestions of the generated errors
+const SUGGESTIONS: &[&str] = &["530.7880", "80", "host", "timeout_seconds"];
+
+/// Generate synthetic error trees with `kind_count` distinct kinds (see [`ExampleKind`]), one
+/// error per kind, and each with `context_count` contexts. Some errors get suggestions, notes, or
+/// an underlying error of another kind. The same seed always gives the same errors.
+pub fn generate(
+    kind_count: usize,
+    context_count: usize,
+    seed: u64,
+) -> Vec<CustomError<'static, ExampleKind>> {
+    let mut random = SplitMix(seed);
+    (0..kind_count)
+        .map(|kind| {
+            let mut error = example_error(&mut random, ExampleKind(kind), context_count);
+            if kind_count > 1 && random.below(3) == 0 {
+                let other = (kind + 1 + random.below(kind_count - 1)) % kind_count;
+                error = error.add_underlying_error(example_error(
+                    &mut random,
+                    ExampleKind(other),
+                    context_count.min(1),
+                ));
+            }
+            error
+        })
+        .collect()
+}
+
+/// Generate a single error of the given kind without underlying errors
+fn example_error(
+    random: &mut SplitMix,
+    kind: ExampleKind,
+    context_count: usize,
+) -> CustomError<'static, ExampleKind> {
+    let (short, long) = DESCRIPTIONS[kind.0 % DESCRIPTIONS.len()];
+    let source = *random.pick(SOURCES);
+    let contexts: Vec<_> = (0..context_count)
+        .map(|_| example_context(random, source))
+        .collect();
+    let mut error = CustomError::new(
+        kind,
+        short,
+        long,
+        contexts.first().cloned().unwrap_or_default(),
+    )
+    .add_contexts(contexts.into_iter().skip(1));
+    if random.below(2) == 0 {
+        error = error.suggestions([*random.pick(SUGGESTIONS)]);
+    }
+    if random.below(4) == 0 {
+        error = error.note("See the format specification for all allowed values");
+    }
+    error
+}
+
+/// Generate a context with a single line from the given source and a highlighted word
+fn example_context(
+    random: &mut SplitMix,
+    (source, lines): (&'static str, &[&'static str]),
+) -> Context<'static> {
+    let line = *random.pick(lines);
+    let words: Vec<(usize, usize)> = line
+        .char_indices()
+        .filter(|(i, c)| {
+            c.is_alphanumeric()
+                && line[..*i]
+                    .chars()
+                    .next_back()
+                    .map_or(true, |p| !p.is_alphanumeric())
+        })
+        .map(|(start, _)| {
+            let length = line[start..]
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '.' || *c == '_')
+                .count();
+            (line[..start].chars().count(), length)
+        })
+        .collect();
+    let (offset, length) = *random.pick(&words);
+    let mut highlight = Highlight::from((0, offset, length));
+    if random.below(2) == 0 {
+        highlight = highlight.comment(*random.pick(COMMENTS));
+    }
+    Context::default()
+        .source(source)
+        .line_index(random.below(1000) as u64)
+        .lines(0, line)
+        .add_highlight(highlight)
+}
+
+/// A small deterministic pseudo-random number generator (SplitMix64)
+struct SplitMix(u64);
+
+impl SplitMix {
+    /// The next pseudo-random number
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A pseudo-random number below the given bound, which has to be above zero
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// A pseudo-random element of the given slice, which cannot be empty
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FullErrorContent;
+
+    #[test]
+    fn deterministic() {
+        let errors = generate(12, 3, 7);
+        assert_eq!(errors, generate(12, 3, 7));
+        assert_ne!(errors, generate(12, 3, 8));
+        assert_eq!(errors.len(), 12);
+        for (index, error) in errors.iter().enumerate() {
+            assert_eq!(error.get_kind(), ExampleKind(index));
+            assert_eq!(error.get_contexts().len(), 3);
+            for context in error.get_contexts().iter() {
+                let highlight = &context.get_highlights()[0];
+                assert!(highlight.text(context).map_or(false, |t| !t.is_empty()));
+            }
+        }
+        assert!(errors.iter().any(|e| !e.get_underlying_errors().is_empty()));
+        assert!(generate(0, 2, 1).is_empty());
+        assert!(generate(1, 0, 1)[0]
+            .get_contexts()
+            .iter()
+            .all(Context::is_empty));
+    }
+}
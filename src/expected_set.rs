@@ -0,0 +1,145 @@
+use std::borrow::Cow;
+
+use crate::{Context, CreateError, ErrorKind};
+
+/// The tokens a parser expected, accumulated while it backtracks over alternatives. Only the
+/// tokens expected at the furthest offset are kept, as that is where the input most likely went
+/// wrong, and every token is kept only once. At the end a single error is made with all expected
+/// tokens in the long description and as suggestions (see [`Self::error`]), instead of an error
+/// per alternative that then has to be merged.
+/// ```
+/// use context_error::*;
+/// let input = "let x 5";
+/// let mut expected = ExpectedSet::default();
+/// expected.add(4, "identifier");
+/// expected.add(6, "=");
+/// expected.add(6, ":");
+/// expected.add(6, "=");
+/// assert_eq!(expected.offset(), Some(6));
+/// assert_eq!(expected.expected(), ["=", ":"]);
+/// let error: BoxedError<BasicKind> = expected.error(
+///     BasicKind::Error,
+///     "Invalid statement",
+///     Context::default().line_index(0).lines(0, input).add_highlight((0, 6..7)),
+/// );
+/// assert_eq!(error.get_long_description(), "Expected one of `=` or `:`");
+/// assert_eq!(error.get_suggestions().as_ref(), ["=", ":"]);
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ExpectedSet {
+    /// The furthest offset any token was expected at
+    offset: Option<usize>,
+    /// The tokens expected at the offset, in the order they were first added
+    expected: Vec<String>,
+}
+
+impl ExpectedSet {
+    /// Record that the given token was expected at the given offset. Tokens expected before the
+    /// furthest offset seen so far are ignored, and a token expected further on replaces all
+    /// tokens seen before.
+    pub fn add(&mut self, offset: usize, token: impl Into<String>) {
+        match self.offset {
+            Some(furthest) if offset < furthest => return,
+            Some(furthest) if offset == furthest => (),
+            _ => {
+                self.offset = Some(offset);
+                self.expected.clear();
+            }
+        }
+        let token = token.into();
+        if !self.expected.contains(&token) {
+            self.expected.push(token);
+        }
+    }
+
+    /// Merge the tokens expected in another branch of the parser into this set
+    pub fn merge(&mut self, other: Self) {
+        if let Some(offset) = other.offset {
+            for token in other.expected {
+                self.add(offset, token);
+            }
+        }
+    }
+
+    /// Check if no token was expected
+    pub fn is_empty(&self) -> bool {
+        self.expected.is_empty()
+    }
+
+    /// The furthest offset any token was expected at
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// The tokens expected at the furthest offset, in the order they were first added
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+
+    /// A description of the expected tokens, like ``Expected one of `a`, `b`, or `c` ``, with the
+    /// tokens marked as code for [`crate::DisplayConfig::inline_markup`]
+    pub fn description(&self) -> String {
+        let code = |token: &String| format!("`{token}`");
+        match self.expected.as_slice() {
+            [] => "Expected nothing".to_string(),
+            [token] => format!("Expected {}", code(token)),
+            [first, second] => format!("Expected one of {} or {}", code(first), code(second)),
+            [rest @ .., last] => format!(
+                "Expected one of {}, or {}",
+                rest.iter().map(code).collect::<Vec<_>>().join(", "),
+                code(last)
+            ),
+        }
+    }
+
+    /// Create a single error with the expected tokens in the long description (see
+    /// [`Self::description`]) and as suggestions
+    pub fn error<'text, E: CreateError<'text, Kind>, Kind: ErrorKind>(
+        &self,
+        kind: Kind,
+        short_desc: impl Into<Cow<'text, str>>,
+        context: Context<'text>,
+    ) -> E {
+        E::new(kind, short_desc, self.description(), context).suggestions(self.expected.clone())
+    }
+}
+
+impl<T: Into<String>> Extend<(usize, T)> for ExpectedSet {
+    fn extend<I: IntoIterator<Item = (usize, T)>>(&mut self, iter: I) {
+        for (offset, token) in iter {
+            self.add(offset, token);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn furthest() {
+        let mut set = ExpectedSet::default();
+        assert!(set.is_empty());
+        set.extend([(3, "a"), (1, "b"), (3, "c"), (3, "a")]);
+        assert_eq!(set.expected(), ["a", "c"]);
+        let mut other = ExpectedSet::default();
+        other.add(5, "d");
+        set.merge(other);
+        assert_eq!(set.offset(), Some(5));
+        assert_eq!(set.expected(), ["d"]);
+        set.merge(ExpectedSet::default());
+        set.add(2, "e");
+        assert_eq!(set.expected(), ["d"]);
+    }
+
+    #[test]
+    fn description() {
+        let mut set = ExpectedSet::default();
+        assert_eq!(set.description(), "Expected nothing");
+        set.add(0, "a");
+        assert_eq!(set.description(), "Expected `a`");
+        set.add(0, "b");
+        set.add(0, "c");
+        assert_eq!(set.description(), "Expected one of `a`, `b`, or `c`");
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{Context, DisplayConfig};
+
+/// A single rendered row of a context, see [`Context::rows`]. This gives the structure of the
+/// rendering, so embedders like TUIs and GUIs can style the rows themselves.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct GutterRow {
+    /// The line number shown in the gutter (1 based), if any
+    pub line_no: Option<u64>,
+    /// The kind of row
+    pub kind: GutterRowKind,
+    /// The text of the row without the gutter
+    pub text: String,
+}
+
+/// The kind of a [`GutterRow`]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum GutterRowKind {
+    /// The location of the context, the sources including it, or the moment it was recorded
+    Header,
+    /// (Part of) a line of the text
+    Source,
+    /// The underlines, comments, and footnotes of the highlights
+    Annotation,
+    /// The closing row, possibly with a note
+    Footer,
+}
+
+/// Render the context and split the rendering in rows
+pub(crate) fn rows(context: &Context<'_>) -> Vec<GutterRow> {
+    let config = DisplayConfig {
+        colour: false,
+        line_numbers: true,
+        ..DisplayConfig::current()
+    };
+    let symbols = config.symbols();
+    let margin = context.margin().max(config.gutter_width);
+    let rendered = config.clone().scoped(|| context.to_string());
+    if context.lines.is_empty() {
+        return if rendered.is_empty() {
+            Vec::new()
+        } else {
+            vec![GutterRow {
+                line_no: context.line_number.map(std::num::NonZeroU64::get),
+                kind: GutterRowKind::Header,
+                text: rendered,
+            }]
+        };
+    }
+    let annotation = symbols.highlight_start_line.trim();
+    let mut seen_source = false;
+    rendered
+        .lines()
+        .map(|row| {
+            let gutter: String = row.chars().take(margin).collect();
+            let symbol: String = row.chars().skip(margin + 1).take(1).collect();
+            let rest: String = row.chars().skip(margin + 2).collect();
+            let kind = if symbol == symbols.top_to_bottom.to_string() {
+                seen_source = true;
+                GutterRowKind::Source
+            } else if symbol == symbols.bottom_endcap.to_string()
+                || symbol == symbols.arc_top_to_right.to_string()
+            {
+                GutterRowKind::Footer
+            } else if symbol == annotation && seen_source {
+                GutterRowKind::Annotation
+            } else {
+                GutterRowKind::Header
+            };
+            let text = match kind {
+                GutterRowKind::Source | GutterRowKind::Annotation => {
+                    rest.strip_prefix(' ').unwrap_or(&rest).to_string()
+                }
+                GutterRowKind::Header | GutterRowKind::Footer => rest
+                    .trim_start_matches(symbols.left_to_right)
+                    .trim_start()
+                    .to_string(),
+            };
+            GutterRow {
+                line_no: gutter.trim().parse().ok(),
+                kind,
+                text,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows() {
+        let context = Context::default()
+            .source("data.csv")
+            .line_index(8)
+            .lines(0, "null,80o0\nnull,20")
+            .add_highlight((0, 5..9, "not a number"));
+        let rows = DisplayConfig::default()
+            .unicode(true)
+            .scoped(|| context.rows().collect::<Vec<_>>());
+        let row = |line_no, kind, text: &str| GutterRow {
+            line_no,
+            kind,
+            text: text.to_string(),
+        };
+        assert_eq!(
+            rows,
+            [
+                row(None, GutterRowKind::Header, "[data.csv:9:6-9]"),
+                row(Some(9), GutterRowKind::Source, "null,80o0"),
+                row(None, GutterRowKind::Annotation, "     ╶──╴not a number"),
+                row(Some(10), GutterRowKind::Source, "null,20"),
+                row(None, GutterRowKind::Footer, ""),
+            ]
+        );
+        assert_eq!(
+            Context::default()
+                .source("data.csv")
+                .line_index(8)
+                .rows()
+                .collect::<Vec<_>>(),
+            [row(Some(9), GutterRowKind::Header, "[data.csv:9]")]
+        );
+        assert_eq!(Context::default().rows().count(), 0);
+    }
+}
@@ -1,8 +1,57 @@
 use std::{
     borrow::Cow,
+    fmt,
     ops::{Bound, RangeBounds},
 };
 
+/// A value that can be used as the optional comment of a highlight, so that comments can be given
+/// as text directly as well as `None` for no comment, without wrapping them in `Some(Cow::…)`.
+/// This is implemented for the common text types only, other types that convert into `Cow<str>`
+/// have to be converted into a `String` or `Cow<str>` first.
+/// ```
+/// use context_error::*;
+/// use std::borrow::Cow;
+/// assert_eq!("here".into_comment(), Some(Cow::Borrowed("here")));
+/// assert_eq!(String::from("here").into_comment(), Some(Cow::Borrowed("here")));
+/// assert_eq!(None.into_comment(), None);
+/// let comment: Box<str> = "here".into();
+/// assert_eq!(Cow::from(comment.into_string()).into_comment(), Some(Cow::Borrowed("here")));
+/// ```
+pub trait IntoComment<'text> {
+    /// Convert into the optional comment
+    fn into_comment(self) -> Option<Cow<'text, str>>;
+}
+
+impl<'text> IntoComment<'text> for &'text str {
+    fn into_comment(self) -> Option<Cow<'text, str>> {
+        Some(Cow::Borrowed(self))
+    }
+}
+
+impl<'text> IntoComment<'text> for &'text String {
+    fn into_comment(self) -> Option<Cow<'text, str>> {
+        Some(Cow::Borrowed(self))
+    }
+}
+
+impl<'text> IntoComment<'text> for String {
+    fn into_comment(self) -> Option<Cow<'text, str>> {
+        Some(Cow::Owned(self))
+    }
+}
+
+impl<'text> IntoComment<'text> for Cow<'text, str> {
+    fn into_comment(self) -> Option<Cow<'text, str>> {
+        Some(self)
+    }
+}
+
+impl<'text> IntoComment<'text> for Option<Cow<'text, str>> {
+    fn into_comment(self) -> Option<Cow<'text, str>> {
+        self
+    }
+}
+
 /// A highlight on a single line. The easiest way of creating these is by using the [From] implementations.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -15,30 +64,85 @@ pub struct Highlight<'text> {
     pub length: usize,
     /// Optional comment to post next to the highlight
     pub comment: Option<Cow<'text, str>>,
+    /// If this is the primary highlight of its context, whose position is shown in the header
+    /// (see [`crate::Context::primary_highlight`])
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub primary: bool,
+    /// The index of another context of the same error this highlight refers to, for example the
+    /// definition of a name that is used here (see [`Self::link`])
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub link: Option<usize>,
+    /// The way this highlight is drawn
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub style: HighlightStyle,
+}
+
+/// The way a highlight is drawn, to distinguish different kinds of annotations, like the span of
+/// an error and a span that is only shown for information
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum HighlightStyle {
+    /// Underline the highlighted text (`╶──╴`)
+    #[default]
+    Underline,
+    /// Draw the bottom of a box around the highlighted text (`╰──╯`)
+    Box,
+    /// Only point at the start of the highlighted text (`^`)
+    Pointer,
+    /// Show the highlighted text with reversed colours in the terminal and as `<mark>` in HTML.
+    /// This is underlined instead if colours are not shown.
+    Background,
+}
+
+impl HighlightStyle {
+    /// The HTML element used for a highlight with this style
+    pub(crate) const fn html_tag(self) -> &'static str {
+        match self {
+            Self::Background => "mark",
+            _ => "span",
+        }
+    }
+
+    /// The additional HTML class used for a highlight with this style
+    pub(crate) const fn html_class(self) -> Option<&'static str> {
+        match self {
+            Self::Underline => None,
+            Self::Box => Some("highlight-box"),
+            Self::Pointer => Some("highlight-pointer"),
+            Self::Background => Some("highlight-background"),
+        }
+    }
 }
 
 /// Create a highlight at the given line, offset, and of the given length without a comment.
 impl<'text> From<(usize, usize, usize)> for Highlight<'text> {
     fn from(value: (usize, usize, usize)) -> Self {
         Self {
+            primary: false,
             line: value.0,
             offset: value.1,
             length: value.2,
             comment: None,
+            link: None,
+            style: HighlightStyle::Underline,
         }
     }
 }
 
-/// Create a highlight at the given line, offset, of the given length, and with a comment.
-impl<'text, Comment: Into<Cow<'text, str>>> From<(usize, usize, usize, Comment)>
-    for Highlight<'text>
-{
+/// Create a highlight at the given line, offset, of the given length, and with an optional comment.
+impl<'text, Comment: IntoComment<'text>> From<(usize, usize, usize, Comment)> for Highlight<'text> {
     fn from(value: (usize, usize, usize, Comment)) -> Self {
         Self {
+            primary: false,
             line: value.0,
             offset: value.1,
             length: value.2,
-            comment: Some(value.3.into()),
+            comment: value.3.into_comment(),
+            link: None,
+            style: HighlightStyle::Underline,
         }
     }
 }
@@ -47,43 +151,49 @@ impl<'text, Comment: Into<Cow<'text, str>>> From<(usize, usize, usize, Comment)>
 impl<'text, Range: RangeBounds<usize>> From<(usize, Range)> for Highlight<'text> {
     fn from(value: (usize, Range)) -> Self {
         let offset = match value.1.start_bound() {
-            Bound::Excluded(n) => n + 1,
+            Bound::Excluded(n) => n.saturating_add(1),
             Bound::Included(n) => *n,
             Bound::Unbounded => 0,
         };
         Self {
+            primary: false,
             line: value.0,
             offset,
             length: match value.1.end_bound() {
                 Bound::Excluded(n) => n.saturating_sub(offset),
-                Bound::Included(n) => n.saturating_sub(offset + 1),
+                Bound::Included(n) => n.saturating_add(1).saturating_sub(offset),
                 Bound::Unbounded => usize::MAX,
             },
             comment: None,
+            link: None,
+            style: HighlightStyle::Underline,
         }
     }
 }
 
-/// Create a highlight at the given line, at the given range, and with a comment.
+/// Create a highlight at the given line, at the given range, and with an optional comment.
 /// Used `u64` here because otherwise this clashes with the `(usize, usize, usize)` option.
-impl<'text, Range: RangeBounds<usize>, Comment: Into<Cow<'text, str>>> From<(u64, Range, Comment)>
+impl<'text, Range: RangeBounds<usize>, Comment: IntoComment<'text>> From<(u64, Range, Comment)>
     for Highlight<'text>
 {
     fn from(value: (u64, Range, Comment)) -> Self {
         let offset = match value.1.start_bound() {
-            Bound::Excluded(n) => n + 1,
+            Bound::Excluded(n) => n.saturating_add(1),
             Bound::Included(n) => *n,
             Bound::Unbounded => 0,
         };
         Self {
+            primary: false,
             line: value.0 as usize,
             offset,
             length: match value.1.end_bound() {
-                Bound::Excluded(n) => n - offset,
-                Bound::Included(n) => n - offset + 1,
+                Bound::Excluded(n) => n.saturating_sub(offset),
+                Bound::Included(n) => n.saturating_add(1).saturating_sub(offset),
                 Bound::Unbounded => usize::MAX,
             },
-            comment: Some(value.2.into()),
+            comment: value.2.into_comment(),
+            link: None,
+            style: HighlightStyle::Underline,
         }
     }
 }
@@ -96,4 +206,209 @@ impl<'text> Highlight<'text> {
             ..self
         }
     }
+
+    /// Set the comment of this highlight, or remove it with `None`
+    /// ```
+    /// use context_error::*;
+    /// let highlight = Highlight::from((0, 5..9)).comment("not a number");
+    /// assert_eq!(highlight, Highlight::from((0, 5..9, "not a number")));
+    /// assert_eq!(highlight.comment(None), Highlight::from((0, 5..9)));
+    /// ```
+    #[must_use]
+    pub fn comment(self, comment: impl IntoComment<'text>) -> Self {
+        Self {
+            comment: comment.into_comment(),
+            ..self
+        }
+    }
+
+    /// Move this highlight to the line with the given index
+    #[must_use]
+    pub fn on_line(self, line: usize) -> Self {
+        Self { line, ..self }
+    }
+
+    /// Set the offset (in chars) of the start of this highlight
+    #[must_use]
+    pub fn with_offset(self, offset: usize) -> Self {
+        Self { offset, ..self }
+    }
+
+    /// Set the length (in chars) of this highlight
+    #[must_use]
+    pub fn with_length(self, length: usize) -> Self {
+        Self { length, ..self }
+    }
+
+    /// Move this highlight the given number of chars to the right, or to the left for negative
+    /// numbers. The offset does not go below zero.
+    #[must_use]
+    pub fn shift(self, offset: isize) -> Self {
+        Self {
+            offset: self.offset.saturating_add_signed(offset),
+            ..self
+        }
+    }
+
+    /// Make this highlight the given number of chars longer, or shorter for negative numbers, for
+    /// example to leave out trailing whitespace of a span from a parser. The length does not go
+    /// below zero.
+    /// ```
+    /// use context_error::*;
+    /// let text = "80o0  ,12";
+    /// let span = Highlight::from((0, 0..6));
+    /// let trimmed = span.extend(-((text[..6].len() - text[..6].trim_end().len()) as isize));
+    /// assert_eq!(trimmed, Highlight::from((0, 0..4)));
+    /// ```
+    #[must_use]
+    pub fn extend(self, length: isize) -> Self {
+        Self {
+            length: self.length.saturating_add_signed(length),
+            ..self
+        }
+    }
+
+    /// Mark this highlight as the primary highlight of its context, the position of the primary
+    /// highlight is shown in the header and compact location of the context.
+    /// ```
+    /// use context_error::*;
+    /// let context = Context::default()
+    ///     .source("data.csv")
+    ///     .line_index(0)
+    ///     .lines(0, "id,value\n1,80o0")
+    ///     .add_highlight((0, 0..2))
+    ///     .add_highlight(Highlight::from((1, 2..6)).primary(true));
+    /// assert_eq!(context.location_string(), "data.csv:2:3-6");
+    /// ```
+    #[must_use]
+    pub fn primary(self, primary: bool) -> Self {
+        Self { primary, ..self }
+    }
+
+    /// Refer to another context of the same error by its index, for example to point from the
+    /// use of a name to its definition. In text a marker with the location of the other context
+    /// is added to the comment, in HTML the highlight links to the other context. Links to the
+    /// context of the highlight itself or to contexts that do not exist are ignored.
+    /// ```
+    /// use context_error::*;
+    /// # DisplayConfig::default().unicode(true).set_thread_local();
+    /// let error = CustomError::new(
+    ///     BasicKind::Error,
+    ///     "Mismatched types",
+    ///     "",
+    ///     Context::default().source("main.rs").line_index(9).lines(0, "let y: u8 = x;").add_highlight(Highlight::from((0, 12..13, "used here")).link(1)),
+    /// ).add_context(Context::default().source("main.rs").line_index(1).lines(0, "let x = \"a\";").add_highlight((0, 4..5, "defined here")));
+    /// assert_eq!(error.to_string(), "error: Mismatched types
+    ///    ╭─[main.rs:10:13]
+    /// 10 │ let y: u8 = x;
+    ///    ╎             ⁃used here → main.rs:2:5
+    /// 2  │ let x = \"a\";
+    ///    ╎     ⁃defined here
+    ///    ╵
+    ///
+    /// ");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn link(self, context: usize) -> Self {
+        Self {
+            link: Some(context),
+            ..self
+        }
+    }
+
+    /// Set the way this highlight is drawn
+    /// ```
+    /// use context_error::*;
+    /// # DisplayConfig::default().unicode(true).set_thread_local();
+    /// let context = Context::default()
+    ///     .lines(0, "let x: u8 = 300;")
+    ///     .add_highlight(Highlight::from((0, 12..15, "too large")))
+    ///     .add_highlight(Highlight::from((0, 7..9, "declared here")).style(HighlightStyle::Box))
+    ///     .add_highlight(Highlight::from((0, 4..5)).style(HighlightStyle::Pointer));
+    /// assert_eq!(context.to_string(), " ╷\n │ let x: u8 = 300;\n ╎     ^  ╰╯   ╶─╴too large\n ╎        ╰─declared here\n ╵");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn style(self, style: HighlightStyle) -> Self {
+        Self { style, ..self }
+    }
+
+    /// Get the text under this highlight in the given context. Returns `None` if the highlight
+    /// does not fit in the text of the context.
+    /// ```
+    /// use context_error::*;
+    /// let context = Context::default().lines(0, "id,value\n1,80o0").add_highlight((1, 2..6));
+    /// assert_eq!(context.get_highlights()[0].text(&context), Some("80o0"));
+    /// assert_eq!(Highlight::from((1, 2..)).text(&context), Some("80o0"));
+    /// assert_eq!(Highlight::from((2, 0..1)).text(&context), None);
+    /// ```
+    pub fn text<'a>(&self, context: &'a crate::Context<'_>) -> Option<&'a str> {
+        let line = context.get_lines().lines().nth(self.line)?;
+        let byte = |chars: usize| {
+            line.char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(line.len()))
+                .nth(chars)
+        };
+        let start = byte(self.offset)?;
+        let end = if self.length == usize::MAX {
+            line.len()
+        } else {
+            byte(self.offset.checked_add(self.length)?)?
+        };
+        Some(&line[start..end])
+    }
+}
+
+/// A highlight that does not fit in the text of a context, see [`crate::Context::try_multiple_highlights`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum HighlightError {
+    /// The highlight is placed on a line that is not in the text
+    LineOutOfRange {
+        /// The index of the highlight in the given highlights
+        highlight: usize,
+        /// The line of the highlight
+        line: usize,
+        /// The number of lines in the text
+        lines: usize,
+    },
+    /// The highlight extends past the end of its line
+    OffsetOutOfRange {
+        /// The index of the highlight in the given highlights
+        highlight: usize,
+        /// The line of the highlight
+        line: usize,
+        /// The end of the highlight (in characters)
+        end: usize,
+        /// The length of the line (in characters)
+        line_length: usize,
+    },
+}
+
+impl fmt::Display for HighlightError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LineOutOfRange {
+                highlight,
+                line,
+                lines,
+            } => write!(
+                f,
+                "Highlight {highlight} is placed on line {line} but the text only has {lines} line{}",
+                if *lines == 1 { "" } else { "s" }
+            ),
+            Self::OffsetOutOfRange {
+                highlight,
+                line,
+                end,
+                line_length,
+            } => write!(
+                f,
+                "Highlight {highlight} ends at {end} but line {line} is only {line_length} characters long"
+            ),
+        }
+    }
 }
+
+impl std::error::Error for HighlightError {}
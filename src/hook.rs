@@ -0,0 +1,64 @@
+use std::sync::RwLock;
+
+use crate::Context;
+
+/// An object safe summary of an error, used to inspect errors without knowing their type, for
+/// example in the hook set with [`set_on_error_created`].
+pub trait ErrorSummary {
+    /// The descriptor of the kind of this error, for example 'error' or 'warning'
+    fn kind_descriptor(&self) -> &'static str;
+
+    /// The short description or title of this error
+    fn title(&self) -> &str;
+
+    /// The long description of this error
+    fn description(&self) -> &str;
+
+    /// The contexts of this error
+    fn contexts(&self) -> &[Context<'_>];
+}
+
+/// The signature of the hook called on error creation
+pub type ErrorCreatedHook = fn(&dyn ErrorSummary);
+
+static ON_ERROR_CREATED: RwLock<Option<ErrorCreatedHook>> = RwLock::new(None);
+
+/// Set a hook that is called whenever an error is created with [`crate::CreateError::new`], for
+/// example to count the diagnostics by kind for metrics or telemetry. Replaces any previously set
+/// hook. Keep the hook cheap as it is called for every error. This includes the errors created
+/// without context after the limit of an [`crate::ErrorLimiter`] is reached.
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use context_error::*;
+/// static ERRORS: AtomicUsize = AtomicUsize::new(0);
+/// set_on_error_created(|summary| {
+///     if summary.kind_descriptor() == "error" {
+///         ERRORS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// });
+/// let _ = BoxedError::new(BasicKind::Error, "Invalid number", "Not a number", Context::default());
+/// assert_eq!(ERRORS.load(Ordering::Relaxed), 1);
+/// remove_on_error_created();
+/// ```
+pub fn set_on_error_created(hook: ErrorCreatedHook) {
+    *ON_ERROR_CREATED
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(hook);
+}
+
+/// Remove the hook set with [`set_on_error_created`]
+pub fn remove_on_error_created() {
+    *ON_ERROR_CREATED
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+}
+
+/// Call the error created hook, if set
+pub(crate) fn error_created(error: &dyn ErrorSummary) {
+    let hook = *ON_ERROR_CREATED
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some(hook) = hook {
+        hook(error);
+    }
+}
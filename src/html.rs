@@ -0,0 +1,237 @@
+/// A default stylesheet for the HTML output of errors (see [`crate::FullErrorContent::to_html`])
+/// with the default [`HtmlSettings`], use [`HtmlSettings::stylesheet`] for other settings.
+///
+/// All colours are defined as CSS variables on the `.ce-error` class (`--ce-background`,
+/// `--ce-foreground`, `--ce-border`, `--ce-dimmed`, `--ce-error`, `--ce-warning`, `--ce-highlight`,
+/// `--ce-highlight-border`, and `--ce-version`) so they can be overridden by the embedding page. A
+/// dark variant is selected automatically with `prefers-color-scheme`. Every error block also has
+/// a class per severity (`ce-severity-error` or `ce-severity-warning`) and for the descriptor of
+/// its kind (`ce-kind-error`).
+pub const HTML_STYLESHEET: &str = include_str!("style.css");
+
+/// The class names used in the HTML output, set with [`crate::DisplayConfig::html`]. By default
+/// all classes start with the prefix `ce-` so they do not collide with the CSS of the embedding
+/// page, for example `ce-error` and `ce-title`. With [`Self::bem`] the classes are named in BEM
+/// style, for example `ce-error__title` and `ce-error--severity-error`. The unprefixed class names
+/// of earlier versions (`context-error` and `title`) can be restored with [`Self::compat`].
+/// ```
+/// use context_error::*;
+/// let error = CustomError::new(BasicKind::Error, "Invalid number", "", Context::default());
+/// let html = |settings| {
+///     DisplayConfig::default().html(settings).set_thread_local();
+///     FullErrorContent::to_html(&error, true)
+/// };
+/// assert!(html(HtmlSettings::default()).contains("<p class='ce-title'>"));
+/// assert!(html(HtmlSettings::default().bem(true)).contains("<p class='ce-error__title'>"));
+/// assert!(html(HtmlSettings::default().prefix("my-")).contains("<p class='my-title'>"));
+/// assert!(html(HtmlSettings::default().compat(true)).contains("<p class='title'>"));
+/// # DisplayConfig::reset_thread_local();
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct HtmlSettings {
+    /// The prefix of all classes
+    pub(crate) prefix: String,
+    /// Name the classes in BEM style
+    pub(crate) bem: bool,
+    /// Use the unprefixed class names of earlier versions
+    pub(crate) compat: bool,
+    /// Add the correlation id of errors as a data attribute
+    pub(crate) correlation_ids: bool,
+}
+
+impl Default for HtmlSettings {
+    fn default() -> Self {
+        Self {
+            prefix: "ce-".to_string(),
+            bem: false,
+            compat: false,
+            correlation_ids: false,
+        }
+    }
+}
+
+impl HtmlSettings {
+    /// Set the prefix of all classes, `ce-` by default
+    #[must_use]
+    pub fn prefix(self, prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            ..self
+        }
+    }
+
+    /// Name the classes in BEM style, with the error as block (`ce-error`), the parts of the error
+    /// as elements (`ce-error__title`), and the severity and kind as modifiers
+    /// (`ce-error--severity-error` and `ce-error--kind-error`)
+    #[must_use]
+    pub fn bem(self, bem: bool) -> Self {
+        Self { bem, ..self }
+    }
+
+    /// Use the unprefixed class names of earlier versions (`context-error`, `title`, and the
+    /// descriptor of the kind as is), this overrides the prefix and BEM style
+    #[must_use]
+    pub fn compat(self, compat: bool) -> Self {
+        Self { compat, ..self }
+    }
+
+    /// Add the correlation id of every error (see [`crate::FullErrorContent::correlation_id`]) as
+    /// the `data-correlation-id` attribute of the element of the error, as 16 hexadecimal digits
+    /// ```
+    /// use context_error::*;
+    /// let error = CustomError::message(BasicKind::Error, "No input");
+    /// DisplayConfig::default().html(HtmlSettings::default().correlation_ids(true)).set_thread_local();
+    /// assert!(FullErrorContent::to_html(&error, true)
+    ///     .contains(&format!(" data-correlation-id='{:016x}'", error.correlation_id())));
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn correlation_ids(self, correlation_ids: bool) -> Self {
+        Self {
+            correlation_ids,
+            ..self
+        }
+    }
+
+    /// Get the class for the given name in the default scheme without prefix, for example `error`,
+    /// `title`, `severity-warning`, or `kind-error`, to refer to the classes from the embedding
+    /// page
+    pub fn class(&self, name: &str) -> String {
+        if self.compat {
+            match name {
+                "errors" => "context-errors".to_string(),
+                "error" => "context-error".to_string(),
+                "underlying-error" => "underlying_error".to_string(),
+                _ => name.strip_prefix("kind-").unwrap_or(name).to_string(),
+            }
+        } else if self.bem {
+            match name {
+                "errors" | "error" => format!("{}{name}", self.prefix),
+                "included" => format!("{}error__source--included", self.prefix),
+                _ if name.starts_with("severity-") || name.starts_with("kind-") => {
+                    format!("{}error--{name}", self.prefix)
+                }
+                _ => format!("{}error__{name}", self.prefix),
+            }
+        } else {
+            format!("{}{name}", self.prefix)
+        }
+    }
+
+    /// Get the default stylesheet ([`HTML_STYLESHEET`]) with the classes of these settings
+    pub fn stylesheet(&self) -> String {
+        let mut output = String::with_capacity(HTML_STYLESHEET.len());
+        let mut rest = HTML_STYLESHEET;
+        while let Some(start) = rest.find(".ce-") {
+            output.push_str(&rest[..=start]);
+            rest = &rest[start + 4..];
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                .unwrap_or(rest.len());
+            output.push_str(&self.class(&rest[..end]));
+            rest = &rest[end..];
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+/// An adapter to use an [`std::io::Write`] where a [`std::fmt::Write`] is expected, for example to
+/// stream HTML output directly to a file or socket with [`crate::FullErrorContent::display_html`].
+/// The IO error that caused a write to fail can be retrieved with [`IoWriter::take_error`].
+#[derive(Debug)]
+pub struct IoWriter<W> {
+    inner: W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> IoWriter<W> {
+    /// Wrap the given writer
+    pub const fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Take the IO error that occurred while writing, if any
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take()
+    }
+
+    /// Get the inner writer back
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Convert the result of a formatting operation into an IO result, retrieving the IO error
+    /// that caused the formatting to fail if any
+    pub(crate) fn finish(&mut self, result: std::fmt::Result) -> std::io::Result<()> {
+        match (result, self.take_error()) {
+            (_, Some(error)) => Err(error),
+            (Ok(()), None) => Ok(()),
+            (Err(_), None) => Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "formatting failed",
+            )),
+        }
+    }
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            std::fmt::Error
+        })
+    }
+}
+
+/// Stream the HTML of all given errors to the writer one by one, wrapped in a
+/// `<div class='ce-errors'>` (see [`HtmlSettings`]). The errors are rendered lazily, so the full
+/// document never has to be built in memory, which allows streaming large reports from web
+/// handlers.
+/// # Errors
+/// If writing to the writer fails.
+pub fn write_html_stream<'text, Kind: crate::ErrorKind, E: crate::FullErrorContent<'text, Kind>>(
+    writer: impl std::io::Write,
+    errors: impl IntoIterator<Item = E>,
+    settings: Option<Kind::Settings>,
+    allow_trim_context: bool,
+) -> std::io::Result<()> {
+    let mut writer = IoWriter::new(writer);
+    let result = (|| {
+        let class = crate::DisplayConfig::with_current(|config| config.html.class("errors"));
+        std::fmt::Write::write_str(&mut writer, &format!("<div class='{class}'>"))?;
+        for error in errors {
+            error.display_html(&mut writer, settings.clone(), allow_trim_context)?;
+        }
+        std::fmt::Write::write_str(&mut writer, "</div>")
+    })();
+    writer.finish(result)?;
+    writer.into_inner().flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classes() {
+        let bem = HtmlSettings::default().bem(true);
+        assert_eq!(bem.class("error"), "ce-error");
+        assert_eq!(bem.class("severity-error"), "ce-error--severity-error");
+        assert_eq!(bem.class("line-number"), "ce-error__line-number");
+        assert_eq!(bem.class("included"), "ce-error__source--included");
+        let compat = HtmlSettings::default().compat(true);
+        assert_eq!(compat.class("errors"), "context-errors");
+        assert_eq!(compat.class("kind-warning"), "warning");
+        assert_eq!(compat.class("underlying-error"), "underlying_error");
+        assert_eq!(HtmlSettings::default().stylesheet(), HTML_STYLESHEET);
+        let stylesheet = bem.prefix("x-").stylesheet();
+        assert!(stylesheet.contains(".x-error.x-error--severity-error { --ce-accent"));
+        assert!(stylesheet.contains(".x-error .x-error__title {"));
+        assert!(!stylesheet.contains(".ce-"));
+        assert!(HtmlSettings::default()
+            .compat(true)
+            .stylesheet()
+            .contains(".context-error .context .source {"));
+    }
+}
@@ -0,0 +1,177 @@
+use std::{collections::BTreeSet, fmt, io, path::Path};
+
+use crate::{ErrorKind, FullErrorContent};
+
+/// Persistent decisions to ignore the errors of a kind in a source, for interactive tools that
+/// let users hide errors they do not want to see again. The kind is matched by its descriptor or
+/// code (see [`ErrorKind::code`]) and the source matches the source of a context exactly or as a
+/// parent directory. Remove the ignored errors with [`Self::remove_ignored`] before combining the
+/// errors, or with [`crate::Report::remove_ignored`].
+///
+/// The file has one decision per line, the kind followed by whitespace and the source. Empty
+/// lines and lines starting with `#` are skipped.
+/// ```
+/// use context_error::*;
+/// let mut ignore = IgnoreFile::parse("# Generated files\nwarning target/\n");
+/// ignore.add("Unused", "src/lib.rs");
+/// let warning = |source| BoxedError::new(BasicKind::Warning, "Unused value", "", Context::default().source(source));
+/// let mut errors = vec![warning("target/out.rs"), warning("src/main.rs")];
+/// ignore.remove_ignored(&mut errors);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(ignore.to_string(), "Unused src/lib.rs\nwarning target/\n");
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IgnoreFile {
+    /// The ignored kinds and sources
+    entries: BTreeSet<(String, String)>,
+}
+
+impl IgnoreFile {
+    /// Parse the text of an ignore file, lines without a source are skipped
+    pub fn parse(text: &str) -> Self {
+        Self {
+            entries: text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with('#'))
+                .filter_map(|line| line.split_once(char::is_whitespace))
+                .map(|(kind, source)| (kind.to_string(), source.trim().to_string()))
+                .collect(),
+        }
+    }
+
+    /// Load the ignore file at the given path, a missing file gives an empty ignore file
+    /// # Errors
+    /// If the file exists but could not be read.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(Self::parse(&text)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Save this ignore file to the given path
+    /// # Errors
+    /// If the file could not be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Ignore the kind with the given descriptor or code in the given source, returns false if
+    /// this was already ignored
+    pub fn add(&mut self, kind: impl Into<String>, source: impl Into<String>) -> bool {
+        self.entries.insert((kind.into(), source.into()))
+    }
+
+    /// Stop ignoring the kind with the given descriptor or code in the given source, returns
+    /// false if this was not ignored
+    pub fn remove(&mut self, kind: &str, source: &str) -> bool {
+        self.entries.remove(&(kind.to_string(), source.to_string()))
+    }
+
+    /// Ignore the kind of the given error, by its code if it has one, in all sources of its
+    /// contexts
+    pub fn add_error<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+        &mut self,
+        error: &E,
+    ) {
+        let kind = error.get_kind();
+        let name = kind.code().unwrap_or_else(|| kind.descriptor().to_string());
+        for context in error.get_contexts().iter() {
+            if let Some(source) = context.get_source() {
+                self.add(name.clone(), source);
+            }
+        }
+    }
+
+    /// Check if the kind with the given descriptor or code is ignored in the given source
+    pub fn ignores(&self, name: &str, source: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|(kind, ignored)| kind == name && Path::new(source).starts_with(ignored))
+    }
+
+    /// Check if the given error is ignored, meaning that the kind of the error is ignored in the
+    /// sources of all its contexts. Errors without any context with a source are never ignored.
+    pub fn is_ignored<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+        &self,
+        error: &E,
+    ) -> bool {
+        let kind = error.get_kind();
+        let code = kind.code();
+        let contexts = error.get_contexts();
+        let mut sources = contexts.iter().filter_map(|c| c.get_source()).peekable();
+        sources.peek().is_some()
+            && sources.all(|source| {
+                self.ignores(kind.descriptor(), source)
+                    || code
+                        .as_ref()
+                        .map_or(false, |code| self.ignores(code, source))
+            })
+    }
+
+    /// Remove all ignored errors, see [`Self::is_ignored`]
+    pub fn remove_ignored<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+        &self,
+        errors: &mut Vec<E>,
+    ) {
+        errors.retain(|error| !self.is_ignored(error));
+    }
+}
+
+impl fmt::Display for IgnoreFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (kind, source) in &self.entries {
+            writeln!(f, "{kind} {source}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_fixtures::{at, error},
+        BasicKind, Context, CreateError,
+    };
+
+    #[test]
+    fn parse() {
+        let ignore =
+            IgnoreFile::parse("error src\n  # comment\nwarning\n\nwarning  my file.txt \n");
+        assert!(ignore.ignores("error", "src/lib.rs"));
+        assert!(ignore.ignores("error", "src"));
+        assert!(!ignore.ignores("error", "src.rs"));
+        assert!(ignore.ignores("warning", "my file.txt"));
+        assert!(!ignore.ignores("warning", "src"));
+        assert_eq!(IgnoreFile::parse(&ignore.to_string()), ignore);
+    }
+
+    #[test]
+    fn ignored() {
+        let unused = |context| error(BasicKind::Warning, "Unused", context);
+        let mut ignore = IgnoreFile::default();
+        ignore.add_error(&unused(at("a.txt", 0)));
+        assert!(!ignore.add("warning", "a.txt"));
+        assert!(ignore.is_ignored(&unused(at("a.txt", 0))));
+        assert!(!ignore.is_ignored(&unused(at("b.txt", 0))));
+        assert!(!ignore.is_ignored(&unused(Context::default())));
+        assert!(!ignore.is_ignored(&unused(at("a.txt", 0)).add_context(at("b.txt", 0))));
+        assert!(ignore.remove("warning", "a.txt"));
+        assert!(!ignore.is_ignored(&unused(at("a.txt", 0))));
+    }
+
+    #[test]
+    fn load_save() {
+        let path =
+            std::env::temp_dir().join(format!("context-error-ignore-{}", std::process::id()));
+        assert_eq!(IgnoreFile::load(&path).unwrap(), IgnoreFile::default());
+        let mut ignore = IgnoreFile::default();
+        ignore.add("error", "data/input.csv");
+        ignore.save(&path).unwrap();
+        assert_eq!(IgnoreFile::load(&path).unwrap(), ignore);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use std::{borrow::Cow, fmt};
+
+use crate::{html_escape, Coloured, Style};
+
+/// A part of a text with inline markup
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum InlineSpan<'a> {
+    /// Plain text
+    Text(&'a str),
+    /// Code, written between backticks
+    Code(&'a str),
+    /// Bold text, written between double asterisks
+    Bold(&'a str),
+}
+
+impl<'a> InlineSpan<'a> {
+    /// The text without the delimiters
+    const fn text(self) -> &'a str {
+        match self {
+            Self::Text(text) | Self::Code(text) | Self::Bold(text) => text,
+        }
+    }
+
+    /// The style used in terminal output
+    const fn style(self) -> Option<Style> {
+        match self {
+            Self::Text(_) => None,
+            Self::Code(_) => Some(Style::Cyan),
+            Self::Bold(_) => Some(Style::Bold),
+        }
+    }
+}
+
+/// Split the text in spans of inline markup. Delimiters without a closing delimiter, or with
+/// nothing in between, are kept as plain text.
+pub(crate) fn parse_inline<'a>(text: &'a str) -> Vec<InlineSpan<'a>> {
+    let mut spans = Vec::new();
+    let mut plain = 0;
+    let mut index = 0;
+    while index < text.len() {
+        let rest = &text[index..];
+        let (delimiter, span): (&str, fn(&'a str) -> InlineSpan<'a>) = if rest.starts_with("**") {
+            ("**", InlineSpan::Bold)
+        } else if rest.starts_with('`') {
+            ("`", InlineSpan::Code)
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+        let inner = index + delimiter.len();
+        if let Some(end) = text[inner..].find(delimiter).filter(|end| *end > 0) {
+            if plain < index {
+                spans.push(InlineSpan::Text(&text[plain..index]));
+            }
+            spans.push(span(&text[inner..inner + end]));
+            index = inner + end + delimiter.len();
+            plain = index;
+        } else {
+            index = inner;
+        }
+    }
+    if plain < text.len() {
+        spans.push(InlineSpan::Text(&text[plain..]));
+    }
+    spans
+}
+
+/// Split the text in runs with their style for terminal output. Inline markup is only parsed if
+/// it is enabled (see [`crate::DisplayConfig::inline_markup`]) and styles are shown, otherwise the
+/// text is kept as written so the delimiters still convey the meaning.
+pub(crate) fn inline_runs(text: &str) -> Vec<(&str, Option<Style>)> {
+    if crate::DisplayConfig::with_current(|config| config.inline_markup) && crate::styles_shown() {
+        parse_inline(text)
+            .into_iter()
+            .map(|span| (span.text(), span.style()))
+            .collect()
+    } else {
+        vec![(text, None)]
+    }
+}
+
+/// The number of characters of the text when displayed in the terminal
+pub(crate) fn inline_width(text: &str) -> usize {
+    inline_runs(text)
+        .iter()
+        .map(|(run, _)| crate::text_width(run))
+        .sum()
+}
+
+/// The text without the markup delimiters if inline markup is enabled, for places where no
+/// styling is possible, like HTML attributes
+pub(crate) fn inline_plain(text: &str) -> Cow<'_, str> {
+    if crate::DisplayConfig::with_current(|config| config.inline_markup) {
+        Cow::Owned(
+            parse_inline(text)
+                .into_iter()
+                .map(InlineSpan::text)
+                .collect(),
+        )
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Write a run of text as split by [`inline_runs`] with its style
+pub(crate) fn write_inline_run(
+    f: &mut impl fmt::Write,
+    run: &str,
+    style: Option<Style>,
+) -> fmt::Result {
+    match style {
+        Some(style) => write!(f, "{}", run.styled(style)),
+        None => f.write_str(run),
+    }
+}
+
+/// Text that is displayed with its inline markup styled in the terminal
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Inline<'a>(pub(crate) &'a str);
+
+impl fmt::Display for Inline<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (run, style) in inline_runs(self.0) {
+            write_inline_run(f, run, style)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write the text as HTML with its inline markup as `<code>` and `<strong>` elements, if inline
+/// markup is enabled
+pub(crate) fn html_inline(writer: &mut impl fmt::Write, text: &str) -> fmt::Result {
+    if !crate::DisplayConfig::with_current(|config| config.inline_markup) {
+        return html_escape(writer, text);
+    }
+    for span in parse_inline(text) {
+        match span {
+            InlineSpan::Text(text) => html_escape(writer, text)?,
+            InlineSpan::Code(text) => {
+                write!(writer, "<code>")?;
+                html_escape(writer, text)?;
+                write!(writer, "</code>")?;
+            }
+            InlineSpan::Bold(text) => {
+                write!(writer, "<strong>")?;
+                html_escape(writer, text)?;
+                write!(writer, "</strong>")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_inline("use `x` or **y**"),
+            [
+                InlineSpan::Text("use "),
+                InlineSpan::Code("x"),
+                InlineSpan::Text(" or "),
+                InlineSpan::Bold("y")
+            ]
+        );
+        assert_eq!(parse_inline("a ` b"), [InlineSpan::Text("a ` b")]);
+        assert_eq!(
+            parse_inline("``**é**"),
+            [InlineSpan::Text("``"), InlineSpan::Bold("é")]
+        );
+        assert_eq!(
+            parse_inline("`**`*"),
+            [InlineSpan::Code("**"), InlineSpan::Text("*")]
+        );
+        assert_eq!(parse_inline(""), []);
+    }
+
+    #[test]
+    fn display() {
+        use crate::{BasicKind, Context, CreateError, CustomError, DisplayConfig};
+        let error = CustomError::new(
+            BasicKind::Error,
+            "Invalid",
+            "Use `0o` here",
+            Context::default()
+                .lines(0, "80o0")
+                .add_highlight((0, 2..3, "**not** a digit")),
+        )
+        .note("see `man`");
+        let (text, runs) = DisplayConfig::default()
+            .inline_markup(true)
+            .scoped(|| crate::record_styles(&error));
+        let styled = |style: Style| -> Vec<&str> {
+            runs.iter()
+                .filter(|(s, _)| *s == style)
+                .map(|(_, range)| &text[range.clone()])
+                .collect()
+        };
+        assert!(text.contains("Use 0o here") && text.contains("not a digit"));
+        assert_eq!(styled(Style::Cyan), ["0o", "man"]);
+        assert_eq!(styled(Style::Bold), ["not"]);
+        let (plain, _) = DisplayConfig::default().scoped(|| crate::record_styles(&error));
+        assert!(plain.contains("Use `0o` here") && plain.contains("**not** a digit"));
+        assert_eq!(
+            DisplayConfig::default()
+                .inline_markup(true)
+                .scoped(|| inline_plain("**not** `a`")),
+            "not a"
+        );
+    }
+}
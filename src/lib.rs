@@ -31,29 +31,128 @@
 
 /// A boxed variant of the error, to ensure a small stack space
 mod boxed_error;
+/// Error definitions loaded from TOML or JSON
+#[cfg(feature = "catalog")]
+mod catalog;
+/// Conversion from and to codespan-reporting diagnostics
+#[cfg(feature = "codespan")]
+mod codespan;
 /// Wrapping the colored functionality
 mod coloured;
 /// Helper methods to merge identical errors
 mod combine;
 /// The context of an error
 mod context;
+/// The text of a context
+mod context_text;
 /// An error with all its properties
 mod custom_error;
+/// Silencing errors with directives in the source
+mod directives;
+/// Settings for displaying errors
+mod display_config;
+/// An object safe error trait
+mod dyn_error;
+/// Transferring batches of errors with their sources between processes
+#[cfg(feature = "json")]
+mod envelope;
 /// Payload trait for error payloads
 mod error_content;
 /// A trait to define errors
 mod error_create;
 /// Trait for error kinds/payloads
 mod error_kind;
+/// Deterministic synthetic errors for documentation, tests, and benchmarks
+pub mod examples;
+/// Accumulating the tokens a parser expected
+mod expected_set;
+/// The rendered rows of a context
+mod gutter_row;
 /// A highlight on a line
 mod highlight;
+/// Hooks that are called on error creation
+mod hook;
+/// Helpers for the HTML output
+mod html;
+/// Persistent decisions to ignore errors
+mod ignore_file;
+/// Lightweight inline markup in comments and descriptions
+mod inline_markup;
+/// Limiting the number of created errors
+mod limiter;
+/// An append-only log of errors on disk
+#[cfg(feature = "json")]
+mod log;
+/// Contexts from memory-mapped files
+#[cfg(feature = "memmap2")]
+mod mapped_source;
+/// Conversion to OpenTelemetry log events
+#[cfg(feature = "otel")]
+mod otel;
+/// A panic hook printing panics as errors
+mod panic;
+/// Creating errors from JSON, TOML, and YAML parse errors
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+mod parse_error;
+/// The commonly used types and all traits, to bring them into scope at once
+pub mod prelude;
+/// Displaying multiple errors together
+mod report;
+/// A versioned wire format for errors
+#[cfg(feature = "serde")]
+mod serialized;
+/// Looking up the text of sources
+mod source_resolver;
+/// Rendering errors as SVG images
+mod svg;
+/// Shared fixtures for the unit tests
+#[cfg(test)]
+mod test_fixtures;
+/// Timestamps of contexts
+#[cfg(feature = "time")]
+mod timestamp;
+/// Accumulating errors of independent validations
+mod validated;
 
 pub use boxed_error::*;
+#[cfg(feature = "catalog")]
+pub use catalog::*;
+#[cfg(feature = "codespan")]
+pub use codespan::*;
 use coloured::*;
 pub use combine::*;
 pub use context::*;
 pub use custom_error::*;
+pub use directives::*;
+pub use display_config::*;
+pub use dyn_error::*;
+#[cfg(feature = "json")]
+pub use envelope::*;
 pub use error_content::*;
 pub use error_create::*;
 pub use error_kind::*;
+pub use expected_set::*;
+pub use gutter_row::*;
 pub use highlight::*;
+pub use hook::*;
+pub use html::*;
+pub use ignore_file::*;
+use inline_markup::*;
+pub use limiter::*;
+#[cfg(feature = "json")]
+pub use log::*;
+#[cfg(feature = "memmap2")]
+pub use mapped_source::*;
+#[cfg(feature = "otel")]
+pub use otel::*;
+pub use panic::*;
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+pub use parse_error::*;
+pub use report::*;
+#[cfg(feature = "serde")]
+pub use serialized::*;
+pub use source_resolver::*;
+use svg::*;
+#[cfg(feature = "time")]
+use timestamp::*;
+pub use validated::*;
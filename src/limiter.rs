@@ -0,0 +1,202 @@
+use std::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    rc::Rc,
+};
+
+use crate::ErrorSummary;
+
+/// The state of an error limiter
+#[derive(Clone, Copy, Debug)]
+struct State {
+    limit: usize,
+    counted: usize,
+    suppressed: usize,
+    counts: fn(&dyn ErrorSummary) -> bool,
+}
+
+thread_local! {
+    static LIMITER: RefCell<Option<Rc<Cell<State>>>> = const { RefCell::new(None) };
+}
+
+/// Limit the number of errors created on the current thread, to stop early when a parser finds
+/// many errors. Once the limit is reached, errors created with [`crate::CreateError::new`] are
+/// cheap sentinel errors without a context. Expensive work, like extracting the context of an
+/// error, can be skipped by checking [`error_limit_reached`]. The limiter is active until it is
+/// dropped. As it is kept per thread there is no synchronisation overhead, and the limiter
+/// cannot be sent to another thread.
+///
+/// Limiters can be nested, only the innermost limiter is active. The errors created while an
+/// inner limiter is active are only counted by the inner limiter, and when it is dropped the outer
+/// limiter continues with its own counts.
+/// ```
+/// use context_error::*;
+/// let limiter = ErrorLimiter::new(2)
+///     .counts(|summary| summary.kind_descriptor() == "error");
+/// let mut errors = Vec::new();
+/// for line in ["1", "a", "b", "c", "d"] {
+///     if line.parse::<u8>().is_err() {
+///         let context = if error_limit_reached() {
+///             Context::default()
+///         } else {
+///             Context::default().lines(0, line)
+///         };
+///         errors.push(BoxedError::new(BasicKind::Error, "Invalid number", "", context));
+///     }
+/// }
+/// assert_eq!(errors.iter().filter(|e| !e.get_contexts().is_empty()).count(), 2);
+/// assert_eq!(limiter.count(), 2);
+/// assert_eq!(limiter.suppressed(), 2);
+/// ```
+/// The limiter cannot be moved to another thread, as it restores the limiter of the thread it
+/// was created on when it is dropped:
+/// ```compile_fail
+/// let limiter = context_error::ErrorLimiter::new(2);
+/// std::thread::spawn(move || drop(limiter));
+/// ```
+#[derive(Debug)]
+pub struct ErrorLimiter {
+    /// The state of this limiter, shared with the thread local while it is active
+    state: Rc<Cell<State>>,
+    /// The limiter that was active on this thread before, restored on drop
+    previous: Option<Rc<Cell<State>>>,
+    /// The limiter restores the state of the thread it was created on, so it cannot be sent
+    not_send: PhantomData<*const ()>,
+}
+
+impl ErrorLimiter {
+    /// Start limiting the number of errors created on this thread to the given number, all
+    /// errors are counted
+    pub fn new(limit: usize) -> Self {
+        let state = Rc::new(Cell::new(State {
+            limit,
+            counted: 0,
+            suppressed: 0,
+            counts: |_| true,
+        }));
+        Self {
+            previous: LIMITER.with(|l| l.replace(Some(state.clone()))),
+            state,
+            not_send: PhantomData,
+        }
+    }
+
+    /// Only count the errors for which the given function returns true, for example only hard
+    /// errors and not warnings
+    #[must_use]
+    pub fn counts(self, counts: fn(&dyn ErrorSummary) -> bool) -> Self {
+        self.state.set(State {
+            counts,
+            ..self.state.get()
+        });
+        self
+    }
+
+    /// The number of errors counted by this limiter
+    pub fn count(&self) -> usize {
+        self.state.get().counted
+    }
+
+    /// The number of errors that were created while this limiter was active after its limit was
+    /// reached
+    pub fn suppressed(&self) -> usize {
+        self.state.get().suppressed
+    }
+}
+
+impl Drop for ErrorLimiter {
+    fn drop(&mut self) {
+        LIMITER.with(|l| *l.borrow_mut() = self.previous.take());
+    }
+}
+
+/// Update the state of the active limiter, if any
+fn update(f: impl FnOnce(&mut State)) {
+    LIMITER.with(|l| {
+        if let Some(cell) = l.borrow().as_ref() {
+            let mut state = cell.get();
+            f(&mut state);
+            cell.set(state);
+        }
+    });
+}
+
+/// Check if the limit of the [`ErrorLimiter`] on this thread is reached, so that errors that
+/// are created now are sentinel errors. Always false if there is no limiter.
+pub fn error_limit_reached() -> bool {
+    LIMITER.with(|l| {
+        l.borrow().as_ref().map_or(false, |s| {
+            let state = s.get();
+            state.counted >= state.limit
+        })
+    })
+}
+
+/// Check if the given new error should be a sentinel error, counting it as suppressed if so
+pub(crate) fn suppress_error(error: &dyn ErrorSummary) -> bool {
+    let mut suppress = false;
+    update(|state| {
+        if state.counted >= state.limit && (state.counts)(error) {
+            state.suppressed += 1;
+            suppress = true;
+        }
+    });
+    suppress
+}
+
+/// Count the given newly created error
+pub(crate) fn count_error(error: &dyn ErrorSummary) {
+    update(|state| {
+        if (state.counts)(error) {
+            state.counted += 1;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_fixtures::error, BasicKind, Context, CreateError, CustomError, FullErrorContent,
+    };
+
+    #[test]
+    fn nested() {
+        let create = |kind| error(kind, "test", Context::default().line_index(1));
+        let outer = ErrorLimiter::new(1).counts(|s| s.kind_descriptor() == "error");
+        let _ = create(BasicKind::Warning);
+        assert!(!error_limit_reached());
+        let _ = create(BasicKind::Error);
+        assert!(error_limit_reached());
+        assert!(!create(BasicKind::Warning).get_contexts().is_empty());
+        assert!(create(BasicKind::Error).get_contexts().is_empty());
+        {
+            let inner = ErrorLimiter::new(1);
+            assert!(!error_limit_reached());
+            let _ = create(BasicKind::Warning);
+            let _ = create(BasicKind::Error);
+            assert_eq!((inner.count(), inner.suppressed()), (1, 1));
+            // The outer limiter keeps its own counts while the inner limiter is active
+            assert_eq!((outer.count(), outer.suppressed()), (1, 1));
+        }
+        assert_eq!((outer.count(), outer.suppressed()), (1, 1));
+        assert!(error_limit_reached());
+        drop(outer);
+        assert!(!error_limit_reached());
+    }
+
+    #[test]
+    fn hook() {
+        thread_local! {
+            static CREATED: Cell<usize> = const { Cell::new(0) };
+        }
+        crate::set_on_error_created(|_| CREATED.with(|c| c.set(c.get() + 1)));
+        let limiter = ErrorLimiter::new(1);
+        for _ in 0..3 {
+            let _ = CustomError::new(BasicKind::Error, "test", "", Context::default());
+        }
+        crate::remove_on_error_created();
+        assert_eq!((limiter.count(), limiter.suppressed()), (1, 2));
+        assert_eq!(CREATED.with(Cell::get), 3);
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{CustomError, ErrorKind, FullErrorContent, Report, SerializedError};
+
+/// An append-only log of errors on disk, for long running batch jobs where the errors should
+/// survive a crash and be inspected later. Every error is written as a single line of JSON in the
+/// versioned [`SerializedError`] format directly when it is added, so at most the error that was
+/// being written when the process died is lost. Read the errors back with [`Self::read`] or
+/// render them with the current [`crate::DisplayConfig`] with [`Self::replay`].
+/// ```
+/// use context_error::*;
+/// # let path = std::env::temp_dir().join(format!("context-error-log-doc-{}", std::process::id()));
+/// let mut log = ErrorLog::open(&path).unwrap();
+/// log.append(&CustomError::new(BasicKind::Error, "Invalid number", "", Context::default().source("data.csv").line_index(1))).unwrap();
+/// // Later, possibly in another process
+/// DisplayConfig::default().unicode(false).set_thread_local();
+/// assert_eq!(
+///     ErrorLog::replay::<BasicKind>(&path).unwrap(),
+///     "error: Invalid number\n[data.csv:2]\n\n"
+/// );
+/// # DisplayConfig::reset_thread_local();
+/// # std::fs::remove_file(path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ErrorLog {
+    /// The file opened in append mode
+    file: File,
+}
+
+impl ErrorLog {
+    /// Open the log at the given path, creating it if it does not exist. New errors are added
+    /// after the errors already in the log.
+    /// # Errors
+    /// If the file could not be opened.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            file: OpenOptions::new().create(true).append(true).open(path)?,
+        })
+    }
+
+    /// Add an error to the end of the log, the error is written as a single line in one write
+    /// # Errors
+    /// If the kind of the error cannot be serialized or the file could not be written.
+    pub fn append<'text, Kind: ErrorKind + Serialize, E: FullErrorContent<'text, Kind>>(
+        &mut self,
+        error: &E,
+    ) -> io::Result<()> {
+        let mut line = serde_json::to_vec(&SerializedError::V1(
+            crate::SerializedErrorV1::from_error(error),
+        ))?;
+        line.push(b'\n');
+        self.file.write_all(&line)
+    }
+
+    /// Read all errors from the log at the given path. A last line without a line ending is
+    /// skipped, as that is an error that was still being written when the process died.
+    /// # Errors
+    /// If the file could not be read or contains a line that is not a valid error.
+    pub fn read<Kind: DeserializeOwned>(
+        path: impl AsRef<Path>,
+    ) -> io::Result<Vec<CustomError<'static, Kind>>> {
+        let text = std::fs::read_to_string(path)?;
+        let complete = text.rfind('\n').map_or("", |end| &text[..end]);
+        complete
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str::<SerializedError<Kind>>(line)
+                    .map(CustomError::from)
+                    .map_err(|e| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Invalid error on line {} of the log: {e}", index + 1),
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Read all errors from the log at the given path (see [`Self::read`]) and render them as a
+    /// [`Report`] with the current [`crate::DisplayConfig`]
+    /// # Errors
+    /// If the file could not be read or contains a line that is not a valid error.
+    pub fn replay<Kind: ErrorKind + Clone + PartialEq + DeserializeOwned + 'static>(
+        path: impl AsRef<Path>,
+    ) -> io::Result<String> {
+        Ok(Report::<_, Kind>::new(Self::read::<Kind>(path)?).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicKind, Context, CreateError};
+
+    #[test]
+    fn append_read() {
+        let path = std::env::temp_dir().join(format!("context-error-log-{}", std::process::id()));
+        let errors = [
+            CustomError::new(
+                BasicKind::Error,
+                "Invalid number",
+                "Not a number",
+                Context::default()
+                    .lines(0, "null,80o0")
+                    .add_highlight((0, 5..9)),
+            ),
+            CustomError::message(BasicKind::Warning, "Empty file"),
+        ];
+        let mut log = ErrorLog::open(&path).unwrap();
+        log.append(&errors[0]).unwrap();
+        drop(log);
+        let mut log = ErrorLog::open(&path).unwrap();
+        log.append(&errors[1]).unwrap();
+        let read = ErrorLog::read::<BasicKind>(&path).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].to_string(), errors[0].to_string());
+        assert_eq!(read[1], errors[1]);
+        // An error that was only partially written is skipped
+        log.file.write_all(b"{\"V1\":{\"kind\"").unwrap();
+        assert_eq!(ErrorLog::read::<BasicKind>(&path).unwrap().len(), 2);
+        log.file.write_all(b"\n").unwrap();
+        assert!(ErrorLog::read::<BasicKind>(&path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
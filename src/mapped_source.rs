@@ -0,0 +1,205 @@
+use std::{borrow::Cow, cell::RefCell, fmt, ops::Range};
+
+use crate::Context;
+
+/// A source backed by a memory-mapped file, to create contexts for huge files without reading
+/// them fully into a string. The index of the line starts is only built as far as needed for
+/// the requested lines, and all slicing is bounds checked so locations outside of the file give
+/// `None` instead of a panic. The text of the contexts borrows from the map, only lines with
+/// invalid UTF-8 are copied.
+///
+/// Mapping a file is unsafe because the file could be changed by another process while it is
+/// mapped, so the map is created by the caller.
+/// ```
+/// use context_error::*;
+/// # let path = std::env::temp_dir().join(format!("context-error-mapped-doc-{}", std::process::id()));
+/// # std::fs::write(&path, "id,value\n1,80\n2,80o0\n").unwrap();
+/// let file = std::fs::File::open(&path).unwrap();
+/// let map = unsafe { memmap2::Mmap::map(&file).unwrap() };
+/// let source = MappedSource::new("data.csv", &map);
+/// let context = source.context_bytes(16..20).unwrap();
+/// # DisplayConfig::default().unicode(true).set_thread_local();
+/// assert_eq!(context.to_string(), "  ╭─[data.csv:3:3-6][B:16—20]\n3 │ 2,80o0\n  ╎   ╶──╴\n  ╵");
+/// # DisplayConfig::reset_thread_local();
+/// # drop(map);
+/// # std::fs::remove_file(path).unwrap();
+/// ```
+pub struct MappedSource<'a> {
+    /// The name of the source used in the contexts
+    name: String,
+    /// The content of the file
+    data: &'a [u8],
+    /// The index of the line starts, built lazily
+    index: RefCell<LineIndex>,
+}
+
+/// The start of the lines of a source, as far as the source has been scanned
+#[derive(Debug)]
+struct LineIndex {
+    /// The byte offsets of the starts of the lines found so far
+    starts: Vec<usize>,
+    /// The number of bytes scanned for line endings
+    scanned: usize,
+}
+
+impl<'a> MappedSource<'a> {
+    /// Create a source with the given name backed by the given map
+    pub fn new(name: impl Into<String>, map: &'a memmap2::Mmap) -> Self {
+        Self {
+            name: name.into(),
+            data: map,
+            index: RefCell::new(LineIndex {
+                starts: vec![0],
+                scanned: 0,
+            }),
+        }
+    }
+
+    /// The size of the file in bytes
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if the file is empty
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Get the byte offset of the start of the line with the given index, scanning the file up to
+    /// that line if it was not scanned yet. Returns `None` if the file has fewer lines.
+    pub fn line_start(&self, line_index: u64) -> Option<usize> {
+        let line_index = usize::try_from(line_index).ok()?;
+        let mut index = self.index.borrow_mut();
+        while index.starts.len() <= line_index && index.scanned < self.data.len() {
+            self.scan_line(&mut index);
+        }
+        index.starts.get(line_index).copied()
+    }
+
+    /// Get the index of the line containing the given byte offset, scanning the file up to that
+    /// offset if it was not scanned yet. Returns `None` if the offset is outside of the file.
+    pub fn line_of(&self, byte: usize) -> Option<u64> {
+        if byte > self.data.len() {
+            return None;
+        }
+        let mut index = self.index.borrow_mut();
+        while index.scanned <= byte && index.scanned < self.data.len() {
+            self.scan_line(&mut index);
+        }
+        Some(index.starts.partition_point(|start| *start <= byte) as u64 - 1)
+    }
+
+    /// Create a context showing the lines in the given range of line indices, without
+    /// highlights. Returns `None` if the range is empty or starts after the end of the file.
+    pub fn context_lines(&self, lines: Range<u64>) -> Option<Context<'a>> {
+        if lines.is_empty() {
+            return None;
+        }
+        let start = self.line_start(lines.start)?;
+        let end = self
+            .line_start(lines.end)
+            .map_or(self.data.len(), |next| next.saturating_sub(1));
+        Some(
+            Context::default()
+                .source(self.name.clone())
+                .line_index(lines.start)
+                .lines(0, self.text(start..end.max(start))?),
+        )
+    }
+
+    /// Create a context showing the line containing the start of the given byte range with the
+    /// range highlighted, if the range spans multiple lines only the part on the first line is
+    /// highlighted. Returns `None` if the range is not within the file.
+    pub fn context_bytes(&self, range: Range<usize>) -> Option<Context<'a>> {
+        if range.start > range.end || range.end > self.data.len() {
+            return None;
+        }
+        let line = self.line_of(range.start)?;
+        let line_start = self.line_start(line)?;
+        let line_end = self
+            .line_start(line + 1)
+            .map_or(self.data.len(), |next| next.saturating_sub(1));
+        let end = range.end.min(line_end);
+        let chars = |bytes: Range<usize>| self.text(bytes).map_or(0, |text| text.chars().count());
+        let text = self.text(line_start..line_end)?;
+        let highlight = (
+            0,
+            chars(line_start..range.start),
+            chars(range.start..end.max(range.start)),
+        );
+        Some(
+            Context::default()
+                .source(self.name.clone())
+                .line_index(line)
+                .lines(0, text)
+                .byte_range(range)
+                .add_highlight(highlight),
+        )
+    }
+
+    /// Get the text of the given byte range, with invalid UTF-8 replaced
+    fn text(&self, range: Range<usize>) -> Option<Cow<'a, str>> {
+        let bytes = self.data.get(range)?;
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        Some(String::from_utf8_lossy(bytes))
+    }
+
+    /// Scan the next line of the file for its line ending
+    fn scan_line(&self, index: &mut LineIndex) {
+        match self.data[index.scanned..].iter().position(|b| *b == b'\n') {
+            Some(position) => {
+                index.scanned += position + 1;
+                index.starts.push(index.scanned);
+            }
+            None => index.scanned = self.data.len(),
+        }
+    }
+}
+
+impl fmt::Debug for MappedSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedSource")
+            .field("name", &self.name)
+            .field("len", &self.data.len())
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DisplayConfig;
+
+    #[test]
+    fn lazy_index() {
+        let path =
+            std::env::temp_dir().join(format!("context-error-mapped-{}", std::process::id()));
+        std::fs::write(&path, b"id,name\r\n1,Andr\xc3\xa9\r\n2,80o0\n\xff\n3,last").unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let map = unsafe { memmap2::Mmap::map(&file).unwrap() };
+        let source = MappedSource::new("data.csv", &map);
+        assert_eq!(source.line_start(1), Some(9));
+        assert_eq!(source.index.borrow().starts, [0, 9]);
+        assert_eq!(source.line_of(20), Some(2));
+        assert_eq!(source.line_of(source.len()), Some(4));
+        assert_eq!(source.line_of(source.len() + 1), None);
+        assert_eq!(source.line_start(5), None);
+        assert!(source.context_bytes(10..100).is_none());
+        assert!(source.context_lines(5..6).is_none());
+        assert_eq!(
+            source.context_lines(1..3).unwrap().get_lines(),
+            "1,André\r\n2,80o0"
+        );
+        assert_eq!(source.context_lines(3..4).unwrap().get_lines(), "\u{FFFD}");
+        assert_eq!(source.context_lines(4..8).unwrap().get_lines(), "3,last");
+        DisplayConfig::default().unicode(true).scoped(|| {
+            assert_eq!(
+                source.context_bytes(11..20).unwrap().to_string(),
+                "  ╭─[data.csv:2:3-7][B:11—20]\n2 │ 1,André\n  ╎   ╶───╴\n  ╵"
+            );
+        });
+        drop(map);
+        std::fs::remove_file(path).unwrap();
+    }
+}
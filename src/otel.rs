@@ -0,0 +1,175 @@
+use opentelemetry::{
+    logs::{AnyValue, LogRecord, Logger, Severity as OtelSeverity},
+    Key,
+};
+
+use crate::{ErrorKind, FullErrorContent, KindDescriptor, Severity};
+
+/// An error as an OpenTelemetry log event, see [`FullErrorContent::to_otel_event`]. The event can
+/// be emitted directly with a [`Logger`] or used to fill a [`LogRecord`] created elsewhere, for
+/// example from a `tracing` layer.
+///
+/// The attributes follow the OpenTelemetry semantic conventions where possible:
+/// * `error.type`: the descriptor and code of the kind, for example `error[UnknownColumn]`
+/// * `code.file.path`, `code.line.number`, and `code.column.number`: the location of the first
+///   context with a location
+/// * `context_error.fingerprint`: the fingerprint of the error as 16 hexadecimal digits (see
+///   [`FullErrorContent::fingerprint`])
+/// * `context_error.description`, `context_error.suggestions`, `context_error.notes`, and
+///   `context_error.version`: the other content of the error, if present
+#[derive(Clone, Debug, PartialEq)]
+pub struct OtelEvent {
+    /// The severity of the event
+    pub severity: OtelSeverity,
+    /// The body of the event, the short description of the error
+    pub body: String,
+    /// The attributes of the event
+    pub attributes: Vec<(Key, AnyValue)>,
+}
+
+impl OtelEvent {
+    /// Create an event for the given error, see [`FullErrorContent::to_otel_event`]
+    pub(crate) fn from_error<'text, Kind: ErrorKind>(
+        error: &(impl FullErrorContent<'text, Kind> + ?Sized),
+        settings: Option<Kind::Settings>,
+    ) -> Self {
+        let severity = match settings.map(|settings| error.effective_severity(settings)) {
+            None | Some(Severity::Error) => OtelSeverity::Error,
+            Some(Severity::Warning) => OtelSeverity::Warn,
+            Some(Severity::Ignored) => OtelSeverity::Debug,
+        };
+        let kind = error.get_kind();
+        let descriptor = KindDescriptor {
+            descriptor: kind.descriptor().to_string(),
+            code: kind.code(),
+        };
+        let mut attributes = vec![
+            (
+                Key::from("error.type"),
+                AnyValue::from(descriptor.to_string()),
+            ),
+            (
+                Key::from("context_error.fingerprint"),
+                AnyValue::from(format!("{:016x}", error.fingerprint())),
+            ),
+        ];
+        let contexts = error.get_contexts();
+        if let Some(context) = contexts.iter().find(|c| !c.is_empty()) {
+            if let Some(source) = context.get_source() {
+                attributes.push((Key::from("code.file.path"), source.to_string().into()));
+            }
+            let (line, column) = context.first_highlight_position();
+            if let Some(line) = line {
+                attributes.push((Key::from("code.line.number"), (line as i64).into()));
+            }
+            if let (Some(_), Some(column)) = (line, column) {
+                attributes.push((Key::from("code.column.number"), (column as i64).into()));
+            }
+        }
+        let description = error.get_long_description();
+        if !description.is_empty() {
+            attributes.push((
+                Key::from("context_error.description"),
+                description.into_owned().into(),
+            ));
+        }
+        for (key, list) in [
+            ("context_error.suggestions", error.get_suggestions()),
+            ("context_error.notes", error.get_notes()),
+        ] {
+            if !list.is_empty() {
+                attributes.push((
+                    Key::from(key),
+                    list.iter().map(ToString::to_string).collect(),
+                ));
+            }
+        }
+        let version = error.get_version();
+        if !version.is_empty() {
+            attributes.push((
+                Key::from("context_error.version"),
+                version.into_owned().into(),
+            ));
+        }
+        Self {
+            severity,
+            body: error.get_short_description().into_owned(),
+            attributes,
+        }
+    }
+
+    /// Fill the severity, body, and attributes of the given log record with this event
+    pub fn fill(self, record: &mut impl LogRecord) {
+        record.set_severity_number(self.severity);
+        record.set_severity_text(self.severity.name());
+        record.set_body(self.body.into());
+        record.add_attributes(self.attributes);
+    }
+
+    /// Emit this event as a log record with the given logger
+    pub fn emit(self, logger: &impl Logger) {
+        let mut record = logger.create_log_record();
+        self.fill(&mut record);
+        logger.emit(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicKind, Context, CreateError, CustomError};
+
+    #[test]
+    fn event() {
+        let error = CustomError::new(
+            BasicKind::Warning,
+            "Invalid number",
+            "",
+            Context::default()
+                .source("data.csv")
+                .line_index(1)
+                .lines(0, "null,80o0")
+                .add_highlight((0, 5..9)),
+        )
+        .suggestions(["800", "80"]);
+        let event = error.to_otel_event(Some(()));
+        assert_eq!(event.severity, OtelSeverity::Warn);
+        assert_eq!(event.body, "Invalid number");
+        let attribute = |key: &str| {
+            event
+                .attributes
+                .iter()
+                .find(|(k, _)| k.as_str() == key)
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(attribute("error.type"), Some(AnyValue::from("warning")));
+        assert_eq!(
+            attribute("code.file.path"),
+            Some(AnyValue::from("data.csv"))
+        );
+        assert_eq!(attribute("code.line.number"), Some(AnyValue::from(2_i64)));
+        assert_eq!(attribute("code.column.number"), Some(AnyValue::from(6_i64)));
+        assert_eq!(
+            attribute("context_error.fingerprint"),
+            Some(AnyValue::from(format!("{:016x}", error.fingerprint())))
+        );
+        assert_eq!(
+            attribute("context_error.suggestions"),
+            Some(AnyValue::from_iter(["800", "80"]))
+        );
+        assert_eq!(attribute("context_error.description"), None);
+        assert_eq!(
+            CustomError::message(BasicKind::Warning, "Wrapper")
+                .add_underlying_error(CustomError::message(BasicKind::Error, "Cause"))
+                .to_otel_event(Some(()))
+                .severity,
+            OtelSeverity::Error
+        );
+        assert_eq!(
+            CustomError::message(BasicKind::Warning, "Unknown settings")
+                .to_otel_event(None)
+                .severity,
+            OtelSeverity::Error
+        );
+    }
+}
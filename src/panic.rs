@@ -0,0 +1,84 @@
+use std::{backtrace::Backtrace, panic::Location};
+
+use crate::{Context, CreateError, CustomError, ErrorKind};
+
+/// Replace the panic hook with one that prints panics as a [`CustomError`] of the given kind, so
+/// that fatal errors of command line tools look like all other errors. The error contains the
+/// panic message, the location of the panic as a compact context, and a backtrace if backtraces
+/// are enabled with the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables (see
+/// [`Backtrace::capture`]).
+/// ```no_run
+/// use context_error::*;
+/// install_panic_hook(BasicKind::Error);
+/// panic!("Configuration file is missing");
+/// // error: The program panicked
+/// // [src/main.rs:3:1]
+/// // Configuration file is missing
+/// ```
+pub fn install_panic_hook<Kind>(kind: Kind)
+where
+    Kind: ErrorKind + Clone + Send + Sync + 'static,
+{
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str));
+        let backtrace = Backtrace::capture();
+        eprintln!(
+            "{}",
+            panic_error(kind.clone(), message, info.location(), Some(&backtrace))
+        );
+    }));
+}
+
+/// Create the error shown for a panic
+pub(crate) fn panic_error<Kind: ErrorKind + Clone + 'static>(
+    kind: Kind,
+    message: Option<&str>,
+    location: Option<&Location<'_>>,
+    backtrace: Option<&Backtrace>,
+) -> CustomError<'static, Kind> {
+    let context = location.map_or_else(Context::default, |location| {
+        Context::default()
+            .source(location.file().to_string())
+            .line_index(u64::from(location.line().saturating_sub(1)))
+            .add_highlight((0, location.column().saturating_sub(1) as usize, 0))
+    });
+    let mut description = message.unwrap_or("No panic message given").to_string();
+    if let Some(backtrace) =
+        backtrace.filter(|b| b.status() == std::backtrace::BacktraceStatus::Captured)
+    {
+        description.push_str(&format!("\n\nBacktrace:\n{backtrace}"));
+    }
+    CustomError::new(kind, "The program panicked", description, context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BasicKind;
+
+    #[test]
+    fn panic_message() {
+        let location = Location::caller();
+        let error = panic_error(
+            BasicKind::Error,
+            Some("Configuration file is missing"),
+            Some(location),
+            Some(&Backtrace::disabled()),
+        );
+        let expected = format!(
+            "error: The program panicked\n[{}:{}:{}]\nConfiguration file is missing\n",
+            location.file(),
+            location.line(),
+            location.column()
+        );
+        assert_eq!(error.to_string(), expected);
+        assert_eq!(
+            panic_error(BasicKind::Error, None, None, None).to_string(),
+            "error: The program panicked\nNo panic message given\n"
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use std::ops::Range;
+
+use crate::{BoxedError, Context, CreateError, ErrorKind};
+
+/// Create an error from a [`serde_json`] error, with a context that highlights the offending
+/// token in the original input. The JSON pointer (RFC 6901) to the value that failed is shown
+/// in the long description, unless the error is at the root of the document.
+/// ```
+/// use context_error::*;
+/// let input = "{\n  \"server\": {\"port\": \"eighty\"}\n}";
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Config { server: Server }
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Server { port: u16 }
+/// let error = serde_json::from_str::<Config>(input).unwrap_err();
+/// let error = json_parse_error(BasicKind::Error, &error, input);
+/// assert_eq!(error.get_long_description(), "invalid type: string \"eighty\", expected u16\nPath: /server/port");
+/// assert_eq!(error.get_contexts()[0].highlighted_texts().next(), Some(Some("\"eighty\"")));
+/// ```
+#[cfg(feature = "json")]
+pub fn json_parse_error<'text, Kind: ErrorKind + Clone + PartialEq + 'text>(
+    kind: Kind,
+    error: &serde_json::Error,
+    input: &'text str,
+) -> BoxedError<'text, Kind> {
+    let message = error.to_string();
+    let message = message
+        .strip_suffix(&format!(
+            " at line {} column {}",
+            error.line(),
+            error.column()
+        ))
+        .unwrap_or(&message);
+    if error.line() == 0 {
+        return BoxedError::new(
+            kind,
+            "Invalid JSON",
+            message.to_string(),
+            Context::default(),
+        );
+    }
+    // The column points just after the offending byte
+    let line_start = line_start(input, error.line() - 1);
+    let offset = floor_char_boundary(input, line_start + error.column().saturating_sub(1));
+    let (context, token) = input_context(input, offset..offset);
+    let pointer = json_pointer(&input[..token.start]);
+    BoxedError::new(kind, "Invalid JSON", with_path(message, &pointer), context)
+}
+
+/// Create an error from a [`toml`] error, with a context that highlights the offending span in
+/// the original input. The dotted key path of the offending key is shown in the long
+/// description, if the error is on a line with a key or inside a table.
+/// ```
+/// use context_error::*;
+/// let input = "[server]\nport = \"eighty\"\n";
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Config { server: Server }
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Server { port: u16 }
+/// let error = toml::from_str::<Config>(input).unwrap_err();
+/// let error = toml_parse_error(BasicKind::Error, &error, input);
+/// assert_eq!(error.get_long_description(), "invalid type: string \"eighty\", expected u16\nPath: server.port");
+/// assert_eq!(error.get_contexts()[0].highlighted_texts().next(), Some(Some("\"eighty\"")));
+/// ```
+#[cfg(feature = "toml")]
+pub fn toml_parse_error<'text, Kind: ErrorKind + Clone + PartialEq + 'text>(
+    kind: Kind,
+    error: &toml::de::Error,
+    input: &'text str,
+) -> BoxedError<'text, Kind> {
+    let message = error.message().trim_end();
+    let Some(span) = error.span() else {
+        return BoxedError::new(
+            kind,
+            "Invalid TOML",
+            message.to_string(),
+            Context::default(),
+        );
+    };
+    let start = floor_char_boundary(input, span.start);
+    let end = floor_char_boundary(input, span.end).max(start);
+    let (context, token) = input_context(input, start..end);
+    let path = toml_key_path(input, token.start);
+    BoxedError::new(kind, "Invalid TOML", with_path(message, &path), context)
+}
+
+/// Create an error from a [`serde_yaml`] error, with a context that highlights the offending
+/// token in the original input. The path to the value that failed is already part of the
+/// message of the error.
+/// ```
+/// use context_error::*;
+/// let input = "server:\n  port: [80\n";
+/// let error = serde_yaml::from_str::<serde_yaml::Value>(input).unwrap_err();
+/// let error = yaml_parse_error(BasicKind::Error, &error, input);
+/// assert_eq!(error.get_short_description(), "Invalid YAML");
+/// assert_eq!(error.get_contexts()[0].get_line_index(), Some(2));
+/// ```
+#[cfg(feature = "yaml")]
+pub fn yaml_parse_error<'text, Kind: ErrorKind + Clone + PartialEq + 'text>(
+    kind: Kind,
+    error: &serde_yaml::Error,
+    input: &'text str,
+) -> BoxedError<'text, Kind> {
+    let message = error.to_string();
+    let Some(location) = error.location() else {
+        return BoxedError::new(kind, "Invalid YAML", message, Context::default());
+    };
+    let message = message
+        .strip_suffix(&format!(
+            " at line {} column {}",
+            location.line(),
+            location.column()
+        ))
+        .unwrap_or(&message)
+        .to_string();
+    let offset = floor_char_boundary(input, location.index());
+    let (context, _) = input_context(input, offset..offset);
+    BoxedError::new(kind, "Invalid YAML", message, context)
+}
+
+/// Add the path to the message, if there is a path
+#[cfg(any(feature = "json", feature = "toml"))]
+fn with_path(message: &str, path: &str) -> String {
+    if path.is_empty() {
+        message.to_string()
+    } else {
+        format!("{message}\nPath: {path}")
+    }
+}
+
+/// The byte offset of the start of the line with the given index, or the end of the input
+#[cfg(feature = "json")]
+fn line_start(input: &str, line_index: usize) -> usize {
+    if line_index == 0 {
+        return 0;
+    }
+    input
+        .match_indices('\n')
+        .nth(line_index - 1)
+        .map_or(input.len(), |(i, _)| i + 1)
+}
+
+/// The largest char boundary in the input at or before the given byte offset
+fn floor_char_boundary(input: &str, offset: usize) -> usize {
+    let mut offset = offset.min(input.len());
+    while !input.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// Check if the character is part of a token, and not whitespace or structural punctuation
+fn is_token(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, ',' | ':' | '[' | ']' | '{' | '}')
+}
+
+/// Create a context with the line of the input containing the start of the given byte range,
+/// highlighting the range (limited to this line). If the range is empty the token around the
+/// start is highlighted instead. The highlighted byte range is returned as well.
+fn input_context(input: &str, range: Range<usize>) -> (Context<'_>, Range<usize>) {
+    let line_start = input[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = input[range.start..]
+        .find('\n')
+        .map_or(input.len(), |i| range.start + i);
+    let line = input[line_start..line_end].trim_end_matches('\r');
+    let line_end = line_start + line.len();
+    let (start, end) = if range.is_empty() {
+        let start = range.start.min(line_end);
+        if input[start..line_end].starts_with(is_token) {
+            (
+                input[line_start..start]
+                    .rfind(|c| !is_token(c))
+                    .map_or(line_start, |i| {
+                        line_start
+                            + i
+                            + input[line_start + i..]
+                                .chars()
+                                .next()
+                                .map_or(1, char::len_utf8)
+                    }),
+                input[start..line_end]
+                    .find(|c| !is_token(c))
+                    .map_or(line_end, |i| start + i),
+            )
+        } else {
+            (
+                start,
+                input[start..line_end]
+                    .chars()
+                    .next()
+                    .map_or(start, |c| start + c.len_utf8()),
+            )
+        }
+    } else {
+        (range.start, range.end.min(line_end))
+    };
+    let offset = input[line_start..start].chars().count();
+    let length = input[start..end].chars().count().max(1);
+    let context = Context::default()
+        .line_index(input[..line_start].matches('\n').count() as u64)
+        .lines(0, line)
+        .add_highlight((0, offset, length));
+    (context, start..end)
+}
+
+/// The JSON pointer to the value at the end of the given (partial) JSON document
+#[cfg(feature = "json")]
+fn json_pointer(partial: &str) -> String {
+    /// An open object with the current key, or an open array with the current index
+    enum Level {
+        Object(Option<String>),
+        Array(usize),
+    }
+    let mut stack = Vec::new();
+    let mut string: Option<String> = None;
+    let mut last_string = None;
+    let mut escaped = false;
+    for c in partial.chars() {
+        if let Some(current) = &mut string {
+            if escaped {
+                escaped = false;
+                current.push(c);
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                last_string = string.take();
+            } else {
+                current.push(c);
+            }
+            continue;
+        }
+        match (c, stack.last_mut()) {
+            ('"', _) => string = Some(String::new()),
+            ('{', _) => stack.push(Level::Object(None)),
+            ('[', _) => stack.push(Level::Array(0)),
+            ('}' | ']', _) => drop(stack.pop()),
+            (':', Some(Level::Object(key))) => *key = last_string.take(),
+            (',', Some(Level::Object(key))) => *key = None,
+            (',', Some(Level::Array(index))) => *index += 1,
+            _ => (),
+        }
+    }
+    let mut pointer = String::new();
+    for level in stack {
+        match level {
+            Level::Object(Some(key)) => {
+                pointer.push('/');
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+            }
+            Level::Object(None) => break,
+            Level::Array(index) => pointer.push_str(&format!("/{index}")),
+        }
+    }
+    pointer
+}
+
+/// The dotted key path of the key at the given byte offset in the TOML input, based on the last
+/// table header before it and the key at the start of its line
+#[cfg(feature = "toml")]
+fn toml_key_path(input: &str, offset: usize) -> String {
+    let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let table = input[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with('['))
+        .map(|header| {
+            header
+                .split('#')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .trim()
+        });
+    let line = &input[line_start..offset];
+    let key = line
+        .find('=')
+        .map(|i| line[..i].trim())
+        .filter(|key| !key.is_empty() && !key.starts_with('#'));
+    match (table, key) {
+        (Some(table), Some(key)) => format!("{table}.{key}"),
+        (Some(path), None) | (None, Some(path)) => path.to_string(),
+        (None, None) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicKind, FullErrorContent, StaticErrorContent};
+
+    #[test]
+    fn token_context() {
+        let input = "a = [1, x2]\r\nb";
+        let (context, token) = input_context(input, 8..8);
+        assert_eq!(token, 8..10);
+        assert_eq!(context.get_lines(), "a = [1, x2]");
+        assert_eq!(context.highlighted_texts().next(), Some(Some("x2")));
+        let (context, _) = input_context(input, 10..10);
+        assert_eq!(context.highlighted_texts().next(), Some(Some("]")));
+        let (context, _) = input_context(input, 11..11);
+        assert_eq!(context.get_highlights()[0].offset, 11);
+        let (context, _) = input_context(input, 14..14);
+        assert_eq!(context.get_line_index(), Some(1));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        assert_eq!(json_pointer(r#"{"a": [1, {"b/c": {"d": "#), "/a/1/b~1c/d");
+        assert_eq!(json_pointer(r#"{"a": [1, 2], "b": "#), "/b");
+        assert_eq!(json_pointer(r#"{"a\"]": 1, "#), "");
+        let input = "{\"a\": 1,}";
+        let error = serde_json::from_str::<serde_json::Value>(input).unwrap_err();
+        let error = json_parse_error(BasicKind::Error, &error, input);
+        assert_eq!(error.get_long_description(), "trailing comma");
+        assert_eq!(
+            error.get_contexts()[0].highlighted_texts().next(),
+            Some(Some("}"))
+        );
+        let error = serde_json::from_str::<serde_json::Value>("").unwrap_err();
+        let error = json_parse_error(BasicKind::Error, &error, "");
+        assert_eq!(error.get_short_description(), "Invalid JSON");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml() {
+        let input = "# comment\n[a.b] # table\nkey = \"value\"\n";
+        assert_eq!(toml_key_path(input, input.len() - 4), "a.b.key");
+        assert_eq!(toml_key_path("key = 1", 6), "key");
+        let input = "key = = 1\n";
+        let error = toml::from_str::<toml::Table>(input).unwrap_err();
+        let error = toml_parse_error(BasicKind::Error, &error, input);
+        assert_eq!(error.get_short_description(), "Invalid TOML");
+        assert_eq!(error.get_contexts()[0].get_line_index(), Some(0));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml() {
+        let input = "a: 1
+b: {c: 2
+";
+        let error = serde_yaml::from_str::<serde_yaml::Value>(input).unwrap_err();
+        let error = yaml_parse_error(BasicKind::Warning, &error, input);
+        assert_eq!(error.get_short_description(), "Invalid YAML");
+        assert!(!error.get_long_description().ends_with("column 1"));
+        assert_eq!(error.get_contexts()[0].get_line_index(), Some(2));
+    }
+}
@@ -0,0 +1,21 @@
+//! ```
+//! use context_error::prelude::*;
+//! fn parse(text: &str) -> CResult<'_, usize> {
+//!     text.parse().map_err(|_| {
+//!         BoxedError::new(
+//!             BasicKind::Error,
+//!             "Invalid number",
+//!             "This is not a number",
+//!             Context::default().lines(0, text),
+//!         )
+//!     })
+//! }
+//! assert_eq!(parse("42"), Ok(42));
+//! assert_eq!(parse("4x").unwrap_err().get_short_description(), "Invalid number");
+//! ```
+
+pub use crate::{
+    BasicKind, BoxedError, CResult, CombineErrorsExtender, Context, CreateError, CustomError,
+    DynError, ErrorKind, ErrorSummary, Errors, FullErrorContent, ResultExt, Spanned,
+    StaticErrorContent,
+};
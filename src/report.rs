@@ -0,0 +1,1047 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt,
+    io::{self, Write},
+    marker::PhantomData,
+};
+
+use crate::{
+    BoxedError, Coloured, Context, DisplayConfig, DisplayFn, ErrorKind, FullErrorContent,
+    IgnoreFile, Severity,
+};
+
+/// A collection of errors that are displayed together, for example all (combined) errors found
+/// while processing a file. The errors are displayed in order separated by an empty line.
+/// ```
+/// use context_error::*;
+/// # DisplayConfig::default().unicode(true).set_thread_local();
+/// let error = |line, column| CustomError::new(
+///     BasicKind::Error,
+///     "Invalid number",
+///     "This column is not a number",
+///     Context::default().source("data.csv").line_index(line).lines(0, "null,80o0").add_highlight((0, column..9)),
+/// ).version("v1.2");
+/// let report = Report::new([error(1, 5), error(4, 6)]).hoist_header(true);
+/// assert_eq!(report.to_string(), "\
+/// Version: v1.2
+/// Source: data.csv
+///
+/// error: Invalid number
+///   ╭─[:2:6-9]
+/// 2 │ null,80o0
+///   ╎      ╶──╴
+///   ╵
+/// This column is not a number
+///
+/// error: Invalid number
+///   ╭─[:5:7-9]
+/// 5 │ null,80o0
+///   ╎       ╶─╴
+///   ╵
+/// This column is not a number
+/// ");
+/// # DisplayConfig::reset_thread_local();
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Report<E, Kind> {
+    /// The errors
+    errors: Vec<E>,
+    /// Show the shared version and source once at the top instead of for every error
+    hoist_header: bool,
+    /// Use the same gutter width for all contexts in the report
+    align_gutters: bool,
+    /// Show errors on the same line once with numbered highlights
+    aggregate_lines: bool,
+    kind: PhantomData<Kind>,
+}
+
+impl<E, Kind> Default for Report<E, Kind> {
+    fn default() -> Self {
+        Self {
+            errors: Vec::new(),
+            hoist_header: false,
+            align_gutters: false,
+            aggregate_lines: false,
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<E, Kind> From<Vec<E>> for Report<E, Kind> {
+    fn from(errors: Vec<E>) -> Self {
+        Self {
+            errors,
+            ..Self::default()
+        }
+    }
+}
+
+impl<E, Kind> FromIterator<E> for Report<E, Kind> {
+    fn from_iter<T: IntoIterator<Item = E>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl<E, Kind> Report<E, Kind> {
+    /// Create a new report with the given errors
+    pub fn new(errors: impl IntoIterator<Item = E>) -> Self {
+        errors.into_iter().collect::<Vec<_>>().into()
+    }
+
+    /// If all errors share the same version, or all their contexts the same source, show the
+    /// version and source once at the top of the report instead of for every error. The source
+    /// is then left out of the headers of the contexts, which still show the line numbers.
+    #[must_use]
+    pub fn hoist_header(self, hoist_header: bool) -> Self {
+        Self {
+            hoist_header,
+            ..self
+        }
+    }
+
+    /// Use the same width for the gutters with line numbers of all contexts in this report,
+    /// including the contexts of underlying errors, so that the gutters line up (see
+    /// [`DisplayConfig::gutter_width`])
+    #[must_use]
+    pub fn align_gutters(self, align_gutters: bool) -> Self {
+        Self {
+            align_gutters,
+            ..self
+        }
+    }
+
+    /// Show the line once if multiple errors point at the same line, with numbered highlights and
+    /// the titles of the errors listed below, instead of repeating the line for every error. Only
+    /// errors with a single context with a single line of text are combined, the other errors are
+    /// displayed as usual. The long descriptions and other details of combined errors are not
+    /// shown.
+    /// ```
+    /// use context_error::*;
+    /// # DisplayConfig::default().unicode(true).set_thread_local();
+    /// let error = |kind, title, columns| CustomError::new(
+    ///     kind,
+    ///     title,
+    ///     "",
+    ///     Context::default().line_index(1).lines(0, "null,80o0,YES").add_highlight((0, columns)),
+    /// );
+    /// let report = Report::new([
+    ///     error(BasicKind::Error, "Invalid number", 5..9),
+    ///     error(BasicKind::Warning, "Unknown value", 10..13),
+    /// ]);
+    /// assert_eq!(report.aggregate_lines(true).to_string(), "  ╷\n\
+    /// 2 │ null,80o0,YES
+    ///   ╎      ╶──╴¹╶─╴²
+    ///   ╵
+    /// ¹ error: Invalid number
+    /// ² warning: Unknown value
+    /// ");
+    /// # DisplayConfig::reset_thread_local();
+    /// ```
+    #[must_use]
+    pub fn aggregate_lines(self, aggregate_lines: bool) -> Self {
+        Self {
+            aggregate_lines,
+            ..self
+        }
+    }
+
+    /// The errors in this report
+    pub fn errors(&self) -> &[E] {
+        &self.errors
+    }
+}
+
+impl<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind> Report<E, Kind> {
+    /// The version shared by all errors, if there are multiple errors
+    fn common_version(&self) -> Option<String> {
+        let (first, rest) = self
+            .errors
+            .split_first()
+            .filter(|(_, rest)| !rest.is_empty())?;
+        let version = first.version_ref();
+        (!version.is_empty() && rest.iter().all(|e| e.version_ref() == version))
+            .then(|| version.into_owned())
+    }
+
+    /// The source shared by all shown contexts of all errors, if there are multiple errors
+    fn common_source(&self) -> Option<String> {
+        if self.errors.len() < 2 {
+            return None;
+        }
+        let mut common: Option<String> = None;
+        for error in &self.errors {
+            for context in error.get_contexts().iter().filter(|c| !c.is_empty()) {
+                let source = context.get_source()?;
+                match &common {
+                    Some(common) if common != source => return None,
+                    Some(_) => (),
+                    None => common = Some(source.to_string()),
+                }
+            }
+        }
+        common
+    }
+
+    /// Sort the errors for presentation, the most severe errors first and then by location and
+    /// title (see [`FullErrorContent::cmp_for_report`]). The sort is stable, so otherwise equal
+    /// errors keep their order.
+    #[must_use]
+    pub fn sort(mut self, settings: Kind::Settings) -> Self {
+        self.errors
+            .sort_by(|a, b| a.cmp_for_report(b, settings.clone()));
+        self
+    }
+
+    /// Remove the errors that are ignored by the given ignore file (see
+    /// [`IgnoreFile::is_ignored`])
+    #[must_use]
+    pub fn remove_ignored(mut self, ignore: &IgnoreFile) -> Self {
+        ignore.remove_ignored(&mut self.errors);
+        self
+    }
+
+    /// The exit code for a program that stops with this report, the highest exit code of all
+    /// errors (see [`ErrorKind::exit_code`]) or 0 if there are no errors. Errors that are not
+    /// errors themselves but have an underlying error (see
+    /// [`FullErrorContent::effective_severity`]) have at least exit code 1.
+    pub fn exit_code(&self, settings: Kind::Settings) -> i32 {
+        self.errors
+            .iter()
+            .map(|e| {
+                e.get_kind().exit_code(settings.clone()).max(i32::from(
+                    e.effective_severity(settings.clone()) == Severity::Error,
+                ))
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// The number of errors per effective severity (see
+    /// [`FullErrorContent::effective_severity`]), severities without errors are left out
+    /// ```
+    /// use context_error::*;
+    /// let report = Report::new([
+    ///     CustomError::message(BasicKind::Warning, "Could not load the config")
+    ///         .add_underlying_error(CustomError::message(BasicKind::Error, "Missing file")),
+    ///     CustomError::message(BasicKind::Warning, "Unused value"),
+    /// ]);
+    /// assert_eq!(
+    ///     report.severity_counts(()).into_iter().collect::<Vec<_>>(),
+    ///     [(Severity::Warning, 1), (Severity::Error, 1)]
+    /// );
+    /// ```
+    pub fn severity_counts(&self, settings: Kind::Settings) -> BTreeMap<Severity, usize> {
+        let mut counts = BTreeMap::new();
+        for error in &self.errors {
+            *counts
+                .entry(error.effective_severity(settings.clone()))
+                .or_default() += 1;
+        }
+        counts
+    }
+
+    /// Print this report to stderr and terminate the program with the exit code of this report
+    /// (see [`Self::exit_code`])
+    /// ```no_run
+    /// use context_error::*;
+    /// let errors: Vec<BoxedError<BasicKind>> = vec![BoxedError::message(BasicKind::Error, "No input")];
+    /// Report::new(errors).exit(());
+    /// ```
+    pub fn exit(&self, settings: Kind::Settings) -> ! {
+        eprint!(
+            "{}",
+            DisplayFn(|f: &mut fmt::Formatter<'_>| self.display(f, Some(settings.clone())))
+        );
+        std::process::exit(self.exit_code(settings))
+    }
+
+    /// Write the errors with an effective severity of [`Severity::Error`] (see
+    /// [`FullErrorContent::effective_severity`]) to `err` and all other errors to `out`, so that a
+    /// CLI can follow the Unix convention of writing only the problems to stderr. Both parts are
+    /// displayed as a report with the same options as this report, keeping the order of the
+    /// errors.
+    /// ```
+    /// use context_error::*;
+    /// let report = Report::new([
+    ///     CustomError::message(BasicKind::Warning, "Unused value"),
+    ///     CustomError::message(BasicKind::Error, "Missing file"),
+    ///     CustomError::message(BasicKind::Warning, "Empty line"),
+    /// ]);
+    /// let (mut out, mut err) = (Vec::new(), Vec::new());
+    /// report.write_split(&mut out, &mut err, ()).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(out).unwrap(),
+    ///     "warning: Unused value\n\n\nwarning: Empty line\n\n"
+    /// );
+    /// assert_eq!(String::from_utf8(err).unwrap(), "error: Missing file\n\n");
+    /// ```
+    /// # Errors
+    /// If any of the writers errors.
+    pub fn write_split(
+        &self,
+        out: &mut impl Write,
+        err: &mut impl Write,
+        settings: Kind::Settings,
+    ) -> io::Result<()>
+    where
+        E: Clone,
+    {
+        let (errors, others): (Vec<E>, Vec<E>) = self
+            .errors
+            .iter()
+            .cloned()
+            .partition(|e| e.effective_severity(settings.clone()) == Severity::Error);
+        for (writer, errors) in [(out as &mut dyn Write, others), (err, errors)] {
+            if errors.is_empty() {
+                continue;
+            }
+            let report = Self {
+                errors,
+                hoist_header: self.hoist_header,
+                align_gutters: self.align_gutters,
+                aggregate_lines: self.aggregate_lines,
+                kind: PhantomData,
+            };
+            write!(
+                writer,
+                "{}",
+                DisplayFn(|f: &mut fmt::Formatter<'_>| report.display(f, Some(settings.clone())))
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Print the errors to stderr and all other errors to stdout, see [`Self::write_split`]
+    /// # Errors
+    /// If writing to stdout or stderr fails, for example because the pipe was closed.
+    pub fn print_split(&self, settings: Kind::Settings) -> io::Result<()>
+    where
+        E: Clone,
+    {
+        self.write_split(&mut io::stdout().lock(), &mut io::stderr().lock(), settings)
+    }
+
+    /// Display this report with the given settings for the error kinds
+    fn display(&self, f: &mut fmt::Formatter<'_>, settings: Option<Kind::Settings>) -> fmt::Result {
+        let (version, source) = if self.hoist_header {
+            (self.common_version(), self.common_source())
+        } else {
+            (None, None)
+        };
+        if let Some(version) = &version {
+            writeln!(f, "{}: {version}", "Version".green())?;
+        }
+        if let Some(source) = &source {
+            writeln!(
+                f,
+                "{}: {}",
+                "Source".blue(),
+                DisplayConfig::map_source(source)
+            )?;
+        }
+        if version.is_some() || source.is_some() {
+            writeln!(f)?;
+        }
+        display_errors(
+            f,
+            &self.errors,
+            DisplayConfig {
+                hoisted_version: version,
+                hoisted_source: source,
+                ..DisplayConfig::current().for_formatter(f)
+            },
+            self.align_gutters,
+            self.aggregate_lines,
+            settings,
+        )
+    }
+}
+
+/// Display the errors separated by an empty line with the given config, optionally with aligned
+/// gutters and with errors on the same line combined (see [`Report::aggregate_lines`])
+fn display_errors<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+    f: &mut fmt::Formatter<'_>,
+    errors: &[E],
+    config: DisplayConfig,
+    align_gutters: bool,
+    aggregate_lines: bool,
+    settings: Option<Kind::Settings>,
+) -> fmt::Result {
+    let groups = if aggregate_lines {
+        line_groups(errors)
+    } else {
+        (0..errors.len()).map(|index| vec![index]).collect()
+    };
+    DisplayConfig {
+        gutter_width: if align_gutters {
+            config.gutter_width.max(max_margin(errors))
+        } else {
+            config.gutter_width
+        },
+        ..config
+    }
+    .scoped(|| {
+        for (index, group) in groups.iter().enumerate() {
+            if index != 0 {
+                writeln!(f)?;
+            }
+            if let [single] = group.as_slice() {
+                crate::fmt_error(&errors[*single], f, settings.clone())?;
+            } else {
+                display_line_group(f, errors, group, settings.clone())?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Group the indices of the errors that have a single context with a single line of text on the
+/// same line, in order of first appearance
+fn line_groups<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+    errors: &[E],
+) -> Vec<Vec<usize>> {
+    let mut groups: Vec<(Option<Context<'text>>, Vec<usize>)> = Vec::new();
+    for (index, error) in errors.iter().enumerate() {
+        let contexts = error.get_contexts();
+        let line = match contexts.as_ref() {
+            [context]
+                if context.get_line_index().is_some()
+                    && context.get_lines().lines().count() == 1 =>
+            {
+                Some(Context {
+                    highlights: Vec::new(),
+                    ..context.clone()
+                })
+            }
+            _ => None,
+        };
+        match groups
+            .iter_mut()
+            .find(|(other, _)| line.is_some() && *other == line)
+        {
+            Some((_, group)) => group.push(index),
+            None => groups.push((line, vec![index])),
+        }
+    }
+    groups.into_iter().map(|(_, group)| group).collect()
+}
+
+/// Display the errors of a group on the same line (see [`line_groups`]) as the line with the
+/// highlights of all errors numbered, followed by the numbered titles of the errors
+fn display_line_group<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+    f: &mut fmt::Formatter<'_>,
+    errors: &[E],
+    group: &[usize],
+    settings: Option<Kind::Settings>,
+) -> fmt::Result {
+    let config = DisplayConfig::current();
+    let marker = |number: usize| {
+        if config.symbol_set == crate::SymbolSet::Unicode {
+            const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+            number
+                .to_string()
+                .chars()
+                .map(|c| SUPERSCRIPTS[c.to_digit(10).unwrap_or_default() as usize])
+                .collect()
+        } else {
+            format!("[{number}]")
+        }
+    };
+    let mut context = Context {
+        highlights: Vec::new(),
+        ..errors[group[0]].get_contexts()[0].clone()
+    };
+    for (number, index) in group.iter().enumerate() {
+        context = context.add_highlights(
+            errors[*index].get_contexts()[0]
+                .get_highlights()
+                .iter()
+                .map(|highlight| crate::Highlight {
+                    comment: Some(marker(number + 1).into()),
+                    primary: number == 0 && highlight.primary,
+                    ..highlight.clone()
+                }),
+        );
+    }
+    crate::display_context_group(f, &[context], None, !f.alternate(), &config)?;
+    writeln!(f)?;
+    for (number, index) in group.iter().enumerate() {
+        let error = &errors[*index];
+        write!(f, "{} ", marker(number + 1))?;
+        crate::display_title(
+            f,
+            &error.get_kind(),
+            settings
+                .clone()
+                .map(|settings| error.effective_severity(settings)),
+            &error.short_description_ref(),
+        )?;
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+impl<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind> fmt::Display for Report<E, Kind> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(f, None)
+    }
+}
+
+/// The largest gutter width needed for any context of the given errors or their underlying errors
+pub(crate) fn max_margin<'text, Kind: ErrorKind, E: FullErrorContent<'text, Kind>>(
+    errors: &[E],
+) -> usize {
+    errors
+        .iter()
+        .map(|e| {
+            e.get_contexts()
+                .iter()
+                .map(Context::margin)
+                .max()
+                .unwrap_or_default()
+                .max(max_margin(&e.get_underlying_errors()))
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+/// The differences between the errors of two runs of a program, see [`diff_reports`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ReportDiff<'a, E, Kind> {
+    /// The errors that are only in the new run
+    added: Vec<&'a E>,
+    /// The errors that are only in the old run
+    removed: Vec<&'a E>,
+    /// The errors of the new run that were also in the old run
+    persisting: Vec<&'a E>,
+    kind: PhantomData<Kind>,
+}
+
+/// Compare the errors of an old and a new run of a program, for example to only show the errors
+/// that are new since the last run of a linter. Errors are matched by their
+/// [`FullErrorContent::fingerprint`], so errors that only moved to another line still match.
+/// Errors with the same fingerprint are matched one to one, so if the new run has an additional
+/// copy of an error that copy is reported as added. The diff displays the added and removed
+/// errors, the persisting errors are only counted.
+/// ```
+/// use context_error::*;
+/// let error = |description, line| CustomError::new(
+///     BasicKind::Error,
+///     description,
+///     "",
+///     Context::default().source("data.csv").line_index(line).lines(0, "null,80o0"),
+/// );
+/// let old = [error("Invalid number", 1), error("Empty column", 4)];
+/// let new = [error("Invalid number", 2), error("Missing column", 8)];
+/// let diff = diff_reports(&old, &new);
+/// assert_eq!(diff.added(), [&new[1]]);
+/// assert_eq!(diff.removed(), [&old[1]]);
+/// assert_eq!(diff.persisting(), [&new[0]]);
+/// ```
+pub fn diff_reports<'a, 'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+    old: &'a [E],
+    new: &'a [E],
+) -> ReportDiff<'a, E, Kind> {
+    let mut unmatched: BTreeMap<u64, VecDeque<usize>> = BTreeMap::new();
+    for (index, error) in old.iter().enumerate() {
+        unmatched
+            .entry(error.fingerprint())
+            .or_default()
+            .push_back(index);
+    }
+    let mut matched = vec![false; old.len()];
+    let (mut added, mut persisting) = (Vec::new(), Vec::new());
+    for error in new {
+        if let Some(index) = unmatched
+            .get_mut(&error.fingerprint())
+            .and_then(VecDeque::pop_front)
+        {
+            matched[index] = true;
+            persisting.push(error);
+        } else {
+            added.push(error);
+        }
+    }
+    let removed = old
+        .iter()
+        .zip(matched)
+        .filter_map(|(error, matched)| (!matched).then_some(error))
+        .collect();
+    ReportDiff {
+        added,
+        removed,
+        persisting,
+        kind: PhantomData,
+    }
+}
+
+impl<'a, E, Kind> ReportDiff<'a, E, Kind> {
+    /// The errors that are only in the new run
+    pub fn added(&self) -> &[&'a E] {
+        &self.added
+    }
+
+    /// The errors that are only in the old run
+    pub fn removed(&self) -> &[&'a E] {
+        &self.removed
+    }
+
+    /// The errors of the new run that were also in the old run
+    pub fn persisting(&self) -> &[&'a E] {
+        &self.persisting
+    }
+
+    /// Check if no errors were added or removed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind> fmt::Display
+    for ReportDiff<'_, E, Kind>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} added, {} removed, {} persisting",
+            self.added.len(),
+            self.removed.len(),
+            self.persisting.len()
+        )?;
+        for (title, errors) in [
+            ("Added".green(), &self.added),
+            ("Removed".red(), &self.removed),
+        ] {
+            if errors.is_empty() {
+                continue;
+            }
+            writeln!(f, "\n{title}:")?;
+            for (index, error) in errors.iter().enumerate() {
+                if index != 0 {
+                    writeln!(f)?;
+                }
+                crate::fmt_error(*error, f, None)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A list of errors that can be displayed directly, for example with `format!("{errors}")`. The
+/// errors are displayed in order separated by an empty line with their gutters aligned, followed by
+/// a summary of the number of errors of each kind. Use [`Report`] for more control over the output.
+/// ```
+/// use context_error::*;
+/// # DisplayConfig::default().unicode(true).set_thread_local();
+/// let error = |kind, line| BoxedError::new(
+///     kind,
+///     "Invalid number",
+///     "This column is not a number",
+///     Context::default().line_index(line).lines(0, "null,80o0").add_highlight((0, 5..9)),
+/// );
+/// let errors: Errors<_> = [error(BasicKind::Error, 8), error(BasicKind::Warning, 11)].into_iter().collect();
+/// assert_eq!(errors.to_string(), "\
+/// error: Invalid number
+///    ╷
+/// 9  │ null,80o0
+///    ╎      ╶──╴
+///    ╵
+/// This column is not a number
+///
+/// warning: Invalid number
+///    ╷
+/// 12 │ null,80o0
+///    ╎      ╶──╴
+///    ╵
+/// This column is not a number
+///
+/// 1 error, 1 warning
+/// ");
+/// # DisplayConfig::reset_thread_local();
+/// ```
+#[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Errors<'text, Kind>(pub Vec<BoxedError<'text, Kind>>);
+
+impl<Kind> Default for Errors<'_, Kind> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<Kind: ErrorKind + Clone> fmt::Debug for Errors<'_, Kind> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.0).finish()
+    }
+}
+
+impl<'text, Kind> From<Vec<BoxedError<'text, Kind>>> for Errors<'text, Kind> {
+    fn from(errors: Vec<BoxedError<'text, Kind>>) -> Self {
+        Self(errors)
+    }
+}
+
+impl<'text, Kind> FromIterator<BoxedError<'text, Kind>> for Errors<'text, Kind> {
+    fn from_iter<T: IntoIterator<Item = BoxedError<'text, Kind>>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'text, Kind> IntoIterator for Errors<'text, Kind> {
+    type Item = BoxedError<'text, Kind>;
+    type IntoIter = std::vec::IntoIter<BoxedError<'text, Kind>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'text, Kind> std::ops::Deref for Errors<'text, Kind> {
+    type Target = Vec<BoxedError<'text, Kind>>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<Kind> std::ops::DerefMut for Errors<'_, Kind> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'text, Kind: ErrorKind + Clone + PartialEq + 'text> fmt::Display for Errors<'text, Kind> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let config = DisplayConfig::current().for_formatter(f);
+        display_errors(f, &self.0, config, true, false, None)?;
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for error in &self.0 {
+            let descriptor = error.get_kind().descriptor();
+            match counts.iter_mut().find(|(d, _)| *d == descriptor) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((descriptor, 1)),
+            }
+        }
+        writeln!(f)?;
+        for (index, (descriptor, count)) in counts.into_iter().enumerate() {
+            if index != 0 {
+                write!(f, ", ")?;
+            }
+            write!(
+                f,
+                "{count} {descriptor}{}",
+                if count == 1 { "" } else { "s" }
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+/// The kind of errors that are counted together in [`statistics`], the descriptor and code (see
+/// [`ErrorKind::code`]) of the kind
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct KindDescriptor {
+    /// The descriptor of the kind, for example `error` or `warning`
+    pub descriptor: String,
+    /// The code of the kind, if any
+    pub code: Option<String>,
+}
+
+impl fmt::Display for KindDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.descriptor)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{code}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// The counts of the errors of one kind, see [`statistics`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CountStats {
+    /// The number of distinct errors
+    pub errors: usize,
+    /// The number of occurrences, the contexts of the errors where errors without any shown
+    /// context count as one occurrence
+    pub occurrences: usize,
+    /// The sources of the contexts of the errors
+    pub sources: BTreeSet<String>,
+}
+
+/// Count the errors per kind, preferably after combining them (see
+/// [`crate::combine_errors`]), to show a summary table or to fail a build if there are too many
+/// errors of a kind. Only the given errors are counted, not their underlying errors.
+/// ```
+/// use context_error::*;
+/// let warning = |source, line| BoxedError::new(
+///     BasicKind::Warning,
+///     "Unused value",
+///     "",
+///     Context::default().source(source).line_index(line),
+/// );
+/// let mut errors = Vec::new();
+/// combine_errors(&mut errors, [warning("a.txt", 1), warning("a.txt", 4), warning("b.txt", 2)]);
+/// let stats = statistics(&errors);
+/// let warnings = &stats[&KindDescriptor { descriptor: "warning".to_string(), code: None }];
+/// assert_eq!((warnings.errors, warnings.occurrences, warnings.sources.len()), (1, 3, 2));
+/// assert!(warnings.occurrences <= 50, "too many warnings");
+/// ```
+pub fn statistics<'text, E: FullErrorContent<'text, Kind>, Kind: ErrorKind>(
+    errors: &[E],
+) -> BTreeMap<KindDescriptor, CountStats> {
+    let mut statistics: BTreeMap<KindDescriptor, CountStats> = BTreeMap::new();
+    for error in errors {
+        let kind = error.get_kind();
+        let stats = statistics
+            .entry(KindDescriptor {
+                descriptor: kind.descriptor().to_string(),
+                code: kind.code(),
+            })
+            .or_default();
+        let contexts = error.get_contexts();
+        stats.errors += 1;
+        stats.occurrences += contexts.iter().filter(|c| !c.is_empty()).count().max(1);
+        stats.sources.extend(
+            contexts
+                .iter()
+                .filter_map(|c| c.get_source().map(ToString::to_string)),
+        );
+    }
+    statistics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_fixtures::{at, error},
+        BasicKind, Context, CreateError, CustomError,
+    };
+
+    #[test]
+    fn no_common_header() {
+        let error = |source, version| {
+            CustomError::new(
+                BasicKind::Warning,
+                "Unused",
+                "test",
+                Context::default().source(source),
+            )
+            .version(version)
+        };
+        let report = Report::new([error("a.csv", "v1"), error("b.csv", "v2")]).hoist_header(true);
+        assert_eq!(
+            report.to_string(),
+            "warning: Unused\n[a.csv]\ntest\nVersion: v1\n\nwarning: Unused\n[b.csv]\ntest\nVersion: v2\n"
+        );
+        let report = Report::new([error("a.csv", "v1"), error("b.csv", "v1")]).hoist_header(true);
+        assert_eq!(
+            report.to_string(),
+            "Version: v1\n\nwarning: Unused\n[a.csv]\ntest\n\nwarning: Unused\n[b.csv]\ntest\n"
+        );
+        let report = Report::new([error("a.csv", "v1"), error("a.csv", "")]).hoist_header(true);
+        assert_eq!(
+            report.to_string(),
+            "Source: a.csv\n\nwarning: Unused\ntest\nVersion: v1\n\nwarning: Unused\ntest\n"
+        );
+        let single: Report<_, BasicKind> = Report::new([error("a.csv", "v1")]).hoist_header(true);
+        assert_eq!(single.to_string(), error("a.csv", "v1").to_string());
+    }
+
+    #[test]
+    fn align_gutters() {
+        let error = |line| {
+            CustomError::new(
+                BasicKind::Error,
+                "Invalid number",
+                "",
+                Context::default()
+                    .line_index(line)
+                    .lines(0, "null,80o0")
+                    .add_highlight((0, 5..9)),
+            )
+        };
+        let report = Report::new([error(8), error(1234)]).align_gutters(true);
+        let gutters: Vec<_> = report
+            .to_string()
+            .lines()
+            .filter_map(|line| line.find(['│', '|']))
+            .collect();
+        assert_eq!(gutters.len(), 2);
+        assert_eq!(gutters[0], gutters[1]);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |kind| BoxedError::message(kind, "test");
+        assert_eq!(Errors::<BasicKind>::default().to_string(), "");
+        let errors = Errors::from(vec![
+            error(BasicKind::Warning),
+            error(BasicKind::Error),
+            error(BasicKind::Warning),
+        ]);
+        assert_eq!(errors.len(), 3);
+        assert!(errors.to_string().ends_with("\n\n2 warnings, 1 error\n"));
+        assert_eq!(errors.to_string().matches("test").count(), 3, "{errors}");
+    }
+
+    #[test]
+    fn exit_code() {
+        let error = |kind| CustomError::message(kind, "test");
+        let report: Report<_, BasicKind> = Report::new([error(BasicKind::Warning)]);
+        assert_eq!(report.exit_code(()), 0);
+        let report: Report<_, BasicKind> =
+            Report::new([error(BasicKind::Warning), error(BasicKind::Error)]);
+        assert_eq!(report.exit_code(()), 1);
+        let report: Report<_, BasicKind> =
+            Report::new([error(BasicKind::Warning).add_underlying_error(error(BasicKind::Error))]);
+        assert_eq!(report.exit_code(()), 1);
+        assert_eq!(
+            Report::<CustomError<BasicKind>, _>::default().exit_code(()),
+            0
+        );
+    }
+
+    #[test]
+    fn aggregate_lines() {
+        let error = |title, line, columns| {
+            CustomError::new(
+                BasicKind::Error,
+                title,
+                "",
+                Context::default()
+                    .source("a.txt")
+                    .line_index(line)
+                    .lines(0, "let x = y;")
+                    .add_highlight((0, columns)),
+            )
+        };
+        let report: Report<_, BasicKind> = Report::new([
+            error("a", 0, 4..5),
+            error("b", 3, 0..3),
+            error("c", 0, 8..9),
+            CustomError::message(BasicKind::Error, "d"),
+        ])
+        .aggregate_lines(true);
+        let text = DisplayConfig::default()
+            .unicode(false)
+            .colour(false)
+            .scoped(|| report.to_string());
+        assert_eq!(text.matches("let x = y;").count(), 2);
+        assert!(text.contains("[1] error: a\n[2] error: c\n\nerror: b\n"));
+        assert!(text.ends_with("\n\nerror: d\n\n"));
+    }
+
+    #[test]
+    fn sort() {
+        let report: Report<_, BasicKind> = Report::new([
+            error(BasicKind::Warning, "a", at("a.txt", 0)),
+            error(BasicKind::Error, "b", Context::default()),
+            error(BasicKind::Error, "c", at("b.txt", 0)),
+            error(BasicKind::Error, "d", at("a.txt", 3)),
+            error(BasicKind::Error, "b", at("a.txt", 3)),
+        ])
+        .sort(());
+        assert_eq!(
+            report
+                .errors()
+                .iter()
+                .map(crate::StaticErrorContent::get_short_description)
+                .collect::<Vec<_>>(),
+            ["b", "d", "c", "b", "a"]
+        );
+        assert!(report.errors()[3].get_contexts()[0].is_empty());
+    }
+
+    #[test]
+    fn statistics() {
+        let stats = super::statistics(&[
+            error(BasicKind::Error, "a", Context::default()),
+            error(BasicKind::Error, "b", Context::default().source("a.txt"))
+                .add_context(at("b.txt", 2))
+                .add_context(at("a.txt", 3)),
+            error(BasicKind::Warning, "c", Context::default().line_index(1)),
+        ]);
+        let descriptor = |descriptor: &str| KindDescriptor {
+            descriptor: descriptor.to_string(),
+            code: None,
+        };
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            stats[&descriptor("error")],
+            CountStats {
+                errors: 2,
+                occurrences: 4,
+                sources: ["a.txt".to_string(), "b.txt".to_string()].into(),
+            }
+        );
+        assert_eq!(
+            stats[&descriptor("warning")],
+            CountStats {
+                errors: 1,
+                occurrences: 1,
+                sources: BTreeSet::new(),
+            }
+        );
+        assert_eq!(descriptor("error").to_string(), "error");
+    }
+
+    #[test]
+    fn diff() {
+        let error = |description, line, text| {
+            CustomError::new(
+                BasicKind::Warning,
+                description,
+                "test",
+                Context::default()
+                    .line_index(line)
+                    .lines(0, text)
+                    .add_highlight((0, 0..2)),
+            )
+        };
+        let old = [
+            error("A", 1, "ab"),
+            error("A", 2, "ab"),
+            error("B", 3, "cd"),
+        ];
+        let new = [
+            error("A", 5, "ab"),
+            error("A", 6, "ac"),
+            error("B", 3, "cd"),
+        ];
+        let diff = diff_reports(&old, &new);
+        assert_eq!(diff.added(), [&new[1]]);
+        assert_eq!(diff.removed(), [&old[1]]);
+        assert_eq!(diff.persisting(), [&new[0], &new[2]]);
+        assert!(!diff.is_empty());
+        assert!(diff
+            .to_string()
+            .starts_with("1 added, 1 removed, 2 persisting\n\nAdded:\nwarning: A\n"));
+        assert!(diff_reports(&new, &new).is_empty());
+        assert_eq!(new[0].fingerprint(), old[0].fingerprint());
+        assert_ne!(new[0].fingerprint(), new[1].fingerprint());
+    }
+
+    #[test]
+    fn write_split() {
+        let report = Report::new([
+            CustomError::message(BasicKind::Warning, "Could not load the config")
+                .add_underlying_error(CustomError::message(BasicKind::Error, "Missing file")),
+            CustomError::message(BasicKind::Warning, "Unused value"),
+        ]);
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        report.write_split(&mut out, &mut err, ()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "warning: Unused value\n\n");
+        assert!(String::from_utf8(err)
+            .unwrap()
+            .starts_with("warning: Could not load the config\n"));
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        Report::<CustomError<BasicKind>, BasicKind>::default()
+            .write_split(&mut out, &mut err, ())
+            .unwrap();
+        assert!(out.is_empty() && err.is_empty());
+    }
+}
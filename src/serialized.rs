@@ -0,0 +1,349 @@
+use std::{borrow::Cow, num::NonZeroU64};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BoxedError, Context, CustomError, ErrorKind, FullErrorContent, Highlight, HighlightStyle,
+};
+
+/// A stable wire format for errors, to persist errors or send them to other processes. The
+/// serde implementations of the error types themselves follow their fields and so change when the
+/// fields change, this format is versioned explicitly with the `v` field so that errors
+/// serialized with an older version of this crate can always be read back. The kind is
+/// serialized with its own serde implementation.
+/// ```
+/// use context_error::*;
+/// # #[cfg(feature = "json")] {
+/// let error = CustomError::new(
+///     BasicKind::Error,
+///     "Invalid number",
+///     "This column is not a number",
+///     Context::default().source("data.csv").line_index(1).lines(0, "null,80o0").add_highlight((0, 5..9)),
+/// );
+/// let json = serde_json::to_string(&SerializedError::from(&error)).unwrap();
+/// assert!(json.starts_with(r#"{"v":"1","kind":"Error","short_description":"Invalid number""#));
+/// let read: SerializedError<BasicKind> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(CustomError::from(read), error);
+/// # }
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "v")]
+pub enum SerializedError<Kind> {
+    /// The first version of the format
+    #[serde(rename = "1")]
+    V1(SerializedErrorV1<Kind>),
+}
+
+/// An error in version 1 of the [`SerializedError`] format
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(bound(deserialize = "Kind: Deserialize<'de>"))]
+pub struct SerializedErrorV1<Kind> {
+    /// The kind of the error
+    pub kind: Kind,
+    /// The short description of the error
+    pub short_description: String,
+    /// The long description of the error
+    #[serde(default)]
+    pub long_description: String,
+    /// The suggestions
+    #[serde(default)]
+    pub suggestions: Vec<String>,
+    /// The notes
+    #[serde(default)]
+    pub notes: Vec<String>,
+    /// The version
+    #[serde(default)]
+    pub version: String,
+    /// The expected version if this error is about an unsupported version, see
+    /// [`crate::StaticErrorContent::get_version_mismatch`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_version: Option<String>,
+    /// The contexts
+    #[serde(default)]
+    pub contexts: Vec<SerializedContextV1>,
+    /// The underlying errors
+    #[serde(default)]
+    pub underlying_errors: Vec<SerializedErrorV1<Kind>>,
+    /// The correlation id as 16 hexadecimal digits, see
+    /// [`Self::from_error_with_correlation_id`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+/// A context in version 1 of the [`SerializedError`] format
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SerializedContextV1 {
+    /// The source of the text
+    #[serde(default)]
+    pub source: Option<String>,
+    /// The line number (1 based) of the first line
+    #[serde(default)]
+    pub line_number: Option<u64>,
+    /// The offset in characters of the text on the first line
+    #[serde(default)]
+    pub first_line_offset: u64,
+    /// The text
+    #[serde(default)]
+    pub lines: String,
+    /// The highlights
+    #[serde(default)]
+    pub highlights: Vec<SerializedHighlightV1>,
+    /// The start and end of the byte range
+    #[serde(default)]
+    pub byte_range: Option<(usize, usize)>,
+    /// The sources including the source of this context with the line number (1 based) of the
+    /// include, the closest include first
+    #[serde(default)]
+    pub included_from: Vec<(String, Option<u64>)>,
+    /// The number of lines of the source if the context points at the whole file
+    #[serde(default)]
+    pub whole_file: Option<u64>,
+    /// The moment the context was recorded in milliseconds since the UNIX epoch, only recorded
+    /// with the `time` feature
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+}
+
+/// A highlight in version 1 of the [`SerializedError`] format
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SerializedHighlightV1 {
+    /// The line index in the text of the context
+    pub line: usize,
+    /// The offset in characters on the line
+    pub offset: usize,
+    /// The length in characters
+    pub length: usize,
+    /// The comment
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// If this is the primary highlight of the context
+    #[serde(default)]
+    pub primary: bool,
+    /// The index of the context this highlight refers to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<usize>,
+    /// The way the highlight is drawn
+    #[serde(default)]
+    pub style: HighlightStyle,
+}
+
+impl<Kind: ErrorKind> SerializedErrorV1<Kind> {
+    /// Convert any error to this format
+    pub fn from_error<'text, E: FullErrorContent<'text, Kind>>(error: &E) -> Self {
+        Self {
+            kind: error.get_kind(),
+            short_description: error.get_short_description().into_owned(),
+            long_description: error.get_long_description().into_owned(),
+            suggestions: error
+                .get_suggestions()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            notes: error.get_notes().iter().map(ToString::to_string).collect(),
+            version: error.get_version().into_owned(),
+            expected_version: error.get_version_mismatch().map(|v| v.expected),
+            contexts: error.get_contexts().iter().map(Into::into).collect(),
+            underlying_errors: error
+                .get_underlying_errors()
+                .iter()
+                .map(Self::from_error)
+                .collect(),
+            correlation_id: None,
+        }
+    }
+
+    /// Convert any error to this format, with the correlation id of the error (see
+    /// [`FullErrorContent::correlation_id`]) so the error can be tracked across runs
+    /// ```
+    /// # #[cfg(feature = "json")] {
+    /// use context_error::*;
+    /// let error = CustomError::message(BasicKind::Error, "No input");
+    /// let json = serde_json::to_string(&SerializedErrorV1::from_error_with_correlation_id(&error)).unwrap();
+    /// assert!(json.ends_with(&format!(r#""correlation_id":"{:016x}"}}"#, error.correlation_id())));
+    /// # }
+    /// ```
+    pub fn from_error_with_correlation_id<'text, E: FullErrorContent<'text, Kind>>(
+        error: &E,
+    ) -> Self {
+        Self {
+            correlation_id: Some(format!("{:016x}", error.correlation_id())),
+            ..Self::from_error(error)
+        }
+    }
+}
+
+impl<Kind: ErrorKind + Clone> From<&CustomError<'_, Kind>> for SerializedError<Kind> {
+    fn from(error: &CustomError<'_, Kind>) -> Self {
+        Self::V1(SerializedErrorV1::from_error(error))
+    }
+}
+
+impl<Kind: ErrorKind + Clone> From<&BoxedError<'_, Kind>> for SerializedError<Kind> {
+    fn from(error: &BoxedError<'_, Kind>) -> Self {
+        Self::V1(SerializedErrorV1::from_error(error))
+    }
+}
+
+impl<Kind> From<SerializedError<Kind>> for CustomError<'static, Kind> {
+    fn from(error: SerializedError<Kind>) -> Self {
+        match error {
+            SerializedError::V1(error) => error.into(),
+        }
+    }
+}
+
+impl<Kind: ErrorKind> From<SerializedError<Kind>> for BoxedError<'static, Kind> {
+    fn from(error: SerializedError<Kind>) -> Self {
+        CustomError::from(error).into()
+    }
+}
+
+impl<Kind> From<SerializedErrorV1<Kind>> for CustomError<'static, Kind> {
+    fn from(error: SerializedErrorV1<Kind>) -> Self {
+        Self {
+            kind: error.kind,
+            short_description: Cow::Owned(error.short_description),
+            long_description: Cow::Owned(error.long_description),
+            suggestions: error.suggestions.into_iter().map(Cow::Owned).collect(),
+            notes: error.notes.into_iter().map(Cow::Owned).collect(),
+            version: Cow::Owned(error.version),
+            expected_version: error.expected_version.map(Cow::Owned),
+            contexts: error.contexts.into_iter().map(Into::into).collect(),
+            underlying_errors: error
+                .underlying_errors
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<&Context<'_>> for SerializedContextV1 {
+    fn from(context: &Context<'_>) -> Self {
+        #[cfg(feature = "time")]
+        let timestamp = context.timestamp.map(|t| {
+            t.0.duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64)
+        });
+        #[cfg(not(feature = "time"))]
+        let timestamp = None;
+        Self {
+            source: context.source.as_ref().map(ToString::to_string),
+            line_number: context.line_number.map(NonZeroU64::get),
+            first_line_offset: context.first_line_offset,
+            lines: context.lines.as_ref().to_string(),
+            highlights: context
+                .highlights
+                .iter()
+                .map(|h| SerializedHighlightV1 {
+                    line: h.line,
+                    offset: h.offset,
+                    length: h.length,
+                    comment: h.comment.as_ref().map(ToString::to_string),
+                    primary: h.primary,
+                    link: h.link,
+                    style: h.style,
+                })
+                .collect(),
+            byte_range: context.byte_range.as_ref().map(|r| (r.start, r.end)),
+            included_from: context
+                .included_from
+                .iter()
+                .map(|(source, line)| (source.to_string(), line.map(NonZeroU64::get)))
+                .collect(),
+            whole_file: context.whole_file,
+            timestamp,
+        }
+    }
+}
+
+impl From<SerializedContextV1> for Context<'static> {
+    fn from(context: SerializedContextV1) -> Self {
+        Self {
+            source: context.source.map(Cow::Owned),
+            line_number: context.line_number.and_then(NonZeroU64::new),
+            first_line_offset: context.first_line_offset,
+            lines: Cow::<'static, str>::Owned(context.lines).into(),
+            highlights: context
+                .highlights
+                .into_iter()
+                .map(|h| Highlight {
+                    line: h.line,
+                    offset: h.offset,
+                    length: h.length,
+                    comment: h.comment.map(Cow::Owned),
+                    primary: h.primary,
+                    link: h.link,
+                    style: h.style,
+                })
+                .collect(),
+            byte_range: context.byte_range.map(|(start, end)| start..end),
+            included_from: context
+                .included_from
+                .into_iter()
+                .map(|(source, line)| (Cow::Owned(source), line.and_then(NonZeroU64::new)))
+                .collect(),
+            whole_file: context.whole_file,
+            #[cfg(feature = "time")]
+            timestamp: context.timestamp.map(|t| {
+                crate::Timestamp(std::time::UNIX_EPOCH + std::time::Duration::from_millis(t))
+            }),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::{BasicKind, CreateError};
+
+    #[test]
+    fn round_trip() {
+        let error = CustomError::new(
+            BasicKind::Warning,
+            "Unused value",
+            "The value is never read",
+            Context::default()
+                .source("b.txt")
+                .line_index(4)
+                .lines(2, "x = 1\ny = 2")
+                .add_highlight((1, 0..1, "here"))
+                .included_from("a.txt", Some(1)),
+        )
+        .suggestions(["_y"])
+        .note("prefix the name with an underscore")
+        .version("v2")
+        .expected_version("v1")
+        .add_context(Context::whole_file("c.txt", 12))
+        .add_underlying_error(CustomError::message(BasicKind::Error, "inner"));
+        let json = serde_json::to_string(&SerializedError::from(&error)).unwrap();
+        let read: SerializedError<BasicKind> = serde_json::from_str(&json).unwrap();
+        assert_eq!(CustomError::from(read.clone()), error);
+        assert_eq!(BoxedError::from(read), BoxedError::from(error.clone()));
+        assert_eq!(
+            SerializedError::from(&BoxedError::from(error.clone())),
+            SerializedError::from(&error)
+        );
+    }
+
+    #[test]
+    fn stable_format() {
+        let json = r#"{"v":"1","kind":"Error","short_description":"Invalid","contexts":[{"source":"a.txt","line_number":3,"lines":"abc","highlights":[{"line":0,"offset":1,"length":1}]}],"future_field":true}"#;
+        let error =
+            CustomError::from(serde_json::from_str::<SerializedError<BasicKind>>(json).unwrap());
+        assert_eq!(
+            error,
+            CustomError::small(BasicKind::Error, "Invalid", "").add_context(
+                Context::default()
+                    .source("a.txt")
+                    .line_index(2)
+                    .lines(0, "abc")
+                    .add_highlight((0, 1, 1))
+            )
+        );
+        assert!(
+            serde_json::from_str::<SerializedError<BasicKind>>(r#"{"v":"0","kind":"Error"}"#)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    borrow::{Borrow, Cow},
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::{BuildHasher, Hash},
+    sync::Arc,
+};
+
+/// A provider of the full text of sources by their name, to fill in the text of contexts that
+/// only point at a location (see [`crate::Context::resolve`]). This allows contexts to be created
+/// cheaply with only a source and a line index or byte range, while the text is looked up in a
+/// virtual file system, an archive, or the unsaved buffers of an editor when the error is
+/// displayed (see [`crate::DisplayConfig::source_resolver`]).
+///
+/// This is implemented for maps from names to texts and for functions that return the text.
+/// ```
+/// use context_error::*;
+/// use std::collections::HashMap;
+/// let sources = HashMap::from([("data.csv", "id,value\n1,80o0")]);
+/// assert_eq!(sources.resolve("data.csv").as_deref(), Some("id,value\n1,80o0"));
+/// let files = |name: &str| (name == "a.txt").then(|| "text".to_string());
+/// assert_eq!(files.resolve("b.txt"), None);
+/// ```
+pub trait SourceResolver {
+    /// Get the full text of the source with the given name, or `None` if the source is unknown
+    fn resolve(&self, source: &str) -> Option<Cow<'_, str>>;
+}
+
+impl<K: Borrow<str> + Hash + Eq, V: AsRef<str>, S: BuildHasher> SourceResolver
+    for HashMap<K, V, S>
+{
+    fn resolve(&self, source: &str) -> Option<Cow<'_, str>> {
+        self.get(source).map(|text| Cow::Borrowed(text.as_ref()))
+    }
+}
+
+impl<K: Borrow<str> + Ord, V: AsRef<str>> SourceResolver for BTreeMap<K, V> {
+    fn resolve(&self, source: &str) -> Option<Cow<'_, str>> {
+        self.get(source).map(|text| Cow::Borrowed(text.as_ref()))
+    }
+}
+
+impl<F: Fn(&str) -> Option<String>> SourceResolver for F {
+    fn resolve(&self, source: &str) -> Option<Cow<'_, str>> {
+        self(source).map(Cow::Owned)
+    }
+}
+
+/// A shared source resolver as stored in the [`crate::DisplayConfig`]. Two resolvers are only
+/// equal if they are the same resolver.
+#[derive(Clone)]
+pub(crate) struct SharedResolver(pub(crate) Arc<dyn SourceResolver + Send + Sync>);
+
+impl fmt::Debug for SharedResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedResolver")
+    }
+}
+
+impl PartialEq for SharedResolver {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedResolver {}
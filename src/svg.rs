@@ -0,0 +1,75 @@
+use std::{fmt::Write, ops::Range};
+
+use crate::{html_escape_char, Style};
+
+/// The width of a single character in pixels
+const CHAR_WIDTH: f64 = 8.4;
+/// The height of a single line in pixels
+const LINE_HEIGHT: usize = 18;
+/// The padding around the text in pixels
+const PADDING: usize = 10;
+
+/// The stylesheet embedded in every SVG, the colours roughly follow common terminal themes
+const SVG_STYLE: &str = "text{font-family:'DejaVu Sans Mono',Menlo,Consolas,monospace;font-size:14px;fill:#1f2328;white-space:pre}\
+.blue{fill:#0969da}.yellow{fill:#9a6700}.red{fill:#cf222e}.green{fill:#1a7f37}.magenta{fill:#8250df}.cyan{fill:#1b7c83}.dimmed{fill:#6e7781}.bold{font-weight:bold}.background{paint-order:stroke;stroke:#fff8c5;stroke-width:0.6em}";
+
+/// Convert rendered text with its styled byte ranges (see [`crate::record_styles`]) into a
+/// standalone SVG image. The ranges are expected to be sorted with enclosing ranges first.
+pub(crate) fn styled_to_svg(text: &str, runs: &[(Style, Range<usize>)]) -> String {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in text.trim_end_matches('\n').split('\n') {
+        lines.push((offset, line.strip_suffix('\r').unwrap_or(line)));
+        offset += line.len() + 1;
+    }
+    let columns = lines
+        .iter()
+        .map(|(_, l)| l.chars().count())
+        .max()
+        .unwrap_or_default();
+    let width = (columns as f64 * CHAR_WIDTH).ceil() as usize + 2 * PADDING;
+    let height = lines.len() * LINE_HEIGHT + 2 * PADDING;
+
+    let mut svg = String::new();
+    // Writing to a String cannot fail
+    let _ = write!(
+        svg,
+        "<svg xmlns='http://www.w3.org/2000/svg' width='{width}' height='{height}' viewBox='0 0 {width} {height}'>\
+        <style>{SVG_STYLE}</style><rect width='100%' height='100%' fill='#ffffff'/>"
+    );
+    for (index, (offset, line)) in lines.iter().enumerate() {
+        let _ = write!(
+            svg,
+            "<text x='{PADDING}' y='{}' xml:space='preserve'>",
+            PADDING + (index + 1) * LINE_HEIGHT - 4
+        );
+        // Styled ranges can span multiple lines, so every line opens the ones that overlap it
+        let mut pending = runs
+            .iter()
+            .filter(|(_, range)| range.start < offset + line.len() && range.end > *offset)
+            .peekable();
+        let mut open: Vec<&Range<usize>> = Vec::new();
+        for (position, c) in line.char_indices().map(|(i, c)| (offset + i, c)) {
+            while open.last().map_or(false, |range| range.end <= position) {
+                open.pop();
+                svg.push_str("</tspan>");
+            }
+            while let Some((style, range)) = pending.next_if(|(_, range)| range.start <= position) {
+                if range.end > position {
+                    let _ = write!(svg, "<tspan class='{}'>", style.name());
+                    open.push(range);
+                }
+            }
+            // Control characters are not allowed in XML
+            if (c as u32) < 32 {
+                svg.push('\u{FFFD}');
+            } else {
+                let _ = html_escape_char(&mut svg, c);
+            }
+        }
+        svg.push_str(&"</tspan>".repeat(open.len()));
+        svg.push_str("</text>");
+    }
+    svg.push_str("</svg>");
+    svg
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+
+use crate::{Color, Context, CreateError, CustomError, ErrorKind};
+
+/// An error of the given kind with the given title and context, without a long description
+pub(crate) fn error<Kind: ErrorKind + Clone + 'static>(
+    kind: Kind,
+    title: &'static str,
+    context: Context<'static>,
+) -> CustomError<'static, Kind> {
+    CustomError::new(kind, title, "", context)
+}
+
+/// A context pointing at the given line index of the given source
+pub(crate) fn at(source: &'static str, line: u64) -> Context<'static> {
+    Context::default().source(source).line_index(line)
+}
+
+/// An error kind to test the optional parts of [`ErrorKind`]. It is an error if the descriptor
+/// is `"error"`, and a warning without a descriptor. It displays as its code, so it can be used
+/// as the source of a [`crate::SourceKind`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct TestKind {
+    pub(crate) descriptor: &'static str,
+    pub(crate) code: Option<&'static str>,
+    pub(crate) colour: Option<Color>,
+    /// Panic when the code is requested, to test rendering panics
+    pub(crate) panics: bool,
+}
+
+impl ErrorKind for TestKind {
+    type Settings = ();
+    fn descriptor(&self) -> &'static str {
+        if self.descriptor.is_empty() {
+            "warning"
+        } else {
+            self.descriptor
+        }
+    }
+    fn is_error(&self, _settings: ()) -> bool {
+        self.descriptor == "error"
+    }
+    fn ignored(&self, _settings: ()) -> bool {
+        false
+    }
+    fn code(&self) -> Option<String> {
+        assert!(!self.panics, "broken code");
+        self.code.map(ToString::to_string)
+    }
+    fn color(&self) -> Option<Color> {
+        self.colour
+    }
+}
+
+impl fmt::Display for TestKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code.unwrap_or_default())
+    }
+}
+
+impl std::error::Error for TestKind {}
@@ -0,0 +1,113 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The moment a context was recorded. Timestamps do not take part in comparisons or hashing, so
+/// that identical errors recorded at different moments can still be merged.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Timestamp(pub(crate) SystemTime);
+
+impl PartialEq for Timestamp {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, _other: &Self) -> std::cmp::Ordering {
+        std::cmp::Ordering::Equal
+    }
+}
+
+impl Hash for Timestamp {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+/// Displayed as an RFC 3339 timestamp in UTC with second precision, for example
+/// `2025-03-14T15:09:26Z`. Moments before the UNIX epoch are shown as the epoch.
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let (days, time) = (seconds / 86_400, seconds % 86_400);
+        // Convert the days since the epoch to a civil date (Howard Hinnant's algorithm)
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let day_of_era = z - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + u64::from(month <= 2);
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            time / 3600,
+            time % 3600 / 60,
+            time % 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn display() {
+        let at = |seconds| Timestamp(UNIX_EPOCH + Duration::from_secs(seconds)).to_string();
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(1_741_964_966), "2025-03-14T15:09:26Z");
+        assert_eq!(at(4_102_444_799), "2099-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn context() {
+        use crate::{BasicKind, Context, CreateError, CustomError, DisplayConfig};
+        let recorded = UNIX_EPOCH + Duration::from_secs(1_741_964_966);
+        let context = Context::default()
+            .source("log.txt")
+            .lines(0, "ERROR")
+            .timestamp(recorded);
+        let error = CustomError::new(BasicKind::Error, "test", "test", context.clone());
+        assert_eq!(error.contexts[0].get_timestamp(), Some(recorded));
+        let later = CustomError::new(BasicKind::Error, "test", "test", Context::default());
+        assert!(later.contexts[0].get_timestamp().is_some());
+        assert_eq!(context, context.clone().timestamp(UNIX_EPOCH));
+
+        DisplayConfig::default()
+            .unicode(true)
+            .timestamps(true)
+            .set_thread_local();
+        assert_eq!(
+            context.to_string(),
+            " ╭─[log.txt]\n ╎ 2025-03-14T15:09:26Z\n │ ERROR\n ╵"
+        );
+        assert_eq!(
+            Context::default()
+                .source("log.txt")
+                .timestamp(recorded)
+                .to_string(),
+            "[log.txt] 2025-03-14T15:09:26Z"
+        );
+        DisplayConfig::default().unicode(true).set_thread_local();
+        assert_eq!(context.to_string(), " ╭─[log.txt]\n │ ERROR\n ╵");
+        DisplayConfig::reset_thread_local();
+    }
+}
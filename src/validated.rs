@@ -0,0 +1,163 @@
+use crate::{combine_errors, CreateError, ErrorKind};
+
+/// The outcome of one or more independent validations, which keeps all errors instead of
+/// stopping at the first one like [`Result`]. Validations are combined with [`Self::zip`] or by
+/// collecting an iterator of validations, after which [`Self::into_result`] merges the errors
+/// with [`combine_errors`].
+/// ```
+/// use context_error::*;
+/// fn number(column: &str) -> Validated<usize, BoxedError<'_, BasicKind>> {
+///     column.parse().map_err(|_| BoxedError::new(
+///         BasicKind::Error,
+///         "Invalid number",
+///         "This column is not a number",
+///         Context::default().lines(0, column),
+///     )).into()
+/// }
+/// let row = "null,80o0,12";
+/// let columns: Validated<Vec<usize>, _> = row.split(',').map(number).collect();
+/// assert_eq!(columns.errors().len(), 2);
+/// let result = number("12").zip(number("80")).map(|(a, b)| a + b).into_result(());
+/// assert_eq!(result, Ok(92));
+/// ```
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Validated<T, E> {
+    /// All validations succeeded
+    Ok(T),
+    /// At least one validation failed, with all errors
+    Errors(Vec<E>),
+}
+
+impl<T, E> Validated<T, E> {
+    /// Check if all validations succeeded
+    pub const fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok(_))
+    }
+
+    /// All errors, empty if all validations succeeded
+    pub fn errors(&self) -> &[E] {
+        match self {
+            Self::Ok(_) => &[],
+            Self::Errors(errors) => errors,
+        }
+    }
+
+    /// Map the value if all validations succeeded
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Validated<U, E> {
+        match self {
+            Self::Ok(value) => Validated::Ok(f(value)),
+            Self::Errors(errors) => Validated::Errors(errors),
+        }
+    }
+
+    /// Run a dependent validation on the value if all validations succeeded
+    pub fn and_then<U>(self, f: impl FnOnce(T) -> Validated<U, E>) -> Validated<U, E> {
+        match self {
+            Self::Ok(value) => f(value),
+            Self::Errors(errors) => Validated::Errors(errors),
+        }
+    }
+
+    /// Combine with an independent validation, the errors of both are kept
+    pub fn zip<U>(self, other: Validated<U, E>) -> Validated<(T, U), E> {
+        match (self, other) {
+            (Self::Ok(a), Validated::Ok(b)) => Validated::Ok((a, b)),
+            (Self::Ok(_), Validated::Errors(errors)) | (Self::Errors(errors), Validated::Ok(_)) => {
+                Validated::Errors(errors)
+            }
+            (Self::Errors(mut errors), Validated::Errors(other)) => {
+                errors.extend(other);
+                Validated::Errors(errors)
+            }
+        }
+    }
+
+    /// Get the value, or all errors merged with [`combine_errors`]. Errors of kinds that are
+    /// ignored with these settings (see [`ErrorKind::ignored`]) are left out.
+    /// # Errors
+    /// If any validation failed.
+    pub fn into_result<'text, Kind: ErrorKind>(self, settings: Kind::Settings) -> Result<T, Vec<E>>
+    where
+        E: CreateError<'text, Kind>,
+    {
+        match self {
+            Self::Ok(value) => Ok(value),
+            Self::Errors(errors) => {
+                let mut combined = Vec::new();
+                combine_errors(
+                    &mut combined,
+                    errors
+                        .into_iter()
+                        .filter(|e| !e.get_kind().ignored(settings.clone())),
+                );
+                Err(combined)
+            }
+        }
+    }
+}
+
+impl<T, E> From<Result<T, E>> for Validated<T, E> {
+    fn from(value: Result<T, E>) -> Self {
+        match value {
+            Ok(value) => Self::Ok(value),
+            Err(error) => Self::Errors(vec![error]),
+        }
+    }
+}
+
+/// Collect independent validations, keeping the errors of all failed validations
+impl<T, E, C: Default + Extend<T>> FromIterator<Validated<T, E>> for Validated<C, E> {
+    fn from_iter<I: IntoIterator<Item = Validated<T, E>>>(iter: I) -> Self {
+        let mut values = C::default();
+        let mut all_errors = Vec::new();
+        for validated in iter {
+            match validated {
+                Validated::Ok(value) => values.extend(std::iter::once(value)),
+                Validated::Errors(errors) => all_errors.extend(errors),
+            }
+        }
+        if all_errors.is_empty() {
+            Self::Ok(values)
+        } else {
+            Self::Errors(all_errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_fixtures::error, BasicKind, Context, CustomError, FullErrorContent};
+
+    #[test]
+    fn merge() {
+        let invalid = |line| {
+            Validated::<(), _>::Errors(vec![error(
+                BasicKind::Error,
+                "Invalid number",
+                Context::default().line_index(line),
+            )])
+        };
+        let result = invalid(1)
+            .zip(Validated::Ok(2))
+            .zip(invalid(2))
+            .into_result(())
+            .unwrap_err();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].get_contexts().len(), 2);
+        let result: Result<Vec<u8>, _> = [
+            Validated::<u8, CustomError<BasicKind>>::Ok(1),
+            Validated::Ok(2),
+        ]
+        .into_iter()
+        .collect::<Validated<_, _>>()
+        .into_result(());
+        assert_eq!(result, Ok(vec![1, 2]));
+        assert_eq!(
+            Validated::<u8, ()>::Ok(1)
+                .and_then(|_| Validated::<u8, ()>::Errors(vec![()]))
+                .errors(),
+            [()]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Golden file tests for the rendering of errors and contexts. Every case is rendered and
+//! compared to `tests/golden/<name>.txt`. Run with `UPDATE_GOLDEN=1` to (re)write the files
+//! after an intended change in the rendering, and review the changes in the files.
+
+use std::path::PathBuf;
+
+use context_error::*;
+
+/// The cases, each with a name and the rendered text
+fn cases() -> Vec<(&'static str, String)> {
+    let csv = "id,value,unit\n1,80o0,mg\n2,,kg\n3,12.5,lbs";
+    vec![
+        (
+            "single_highlight",
+            Context::default()
+                .source("data.csv")
+                .line_index(1)
+                .lines(0, "1,80o0,mg")
+                .add_highlight((0, 2..6, "not a number"))
+                .to_string(),
+        ),
+        (
+            "multiple_lines",
+            Context::default()
+                .source("data.csv")
+                .line_index(0)
+                .lines(0, csv)
+                .add_highlight((1, 2..6, "not a number"))
+                .add_highlight((2, 2..2, "empty"))
+                .add_highlight((3, 7..10, "unknown unit"))
+                .to_string(),
+        ),
+        (
+            "primary_highlight",
+            Context::default()
+                .source("data.csv")
+                .line_index(0)
+                .lines(0, csv)
+                .add_highlight((1, 2..6))
+                .add_highlight(Highlight::from((3, 7..10)).primary(true))
+                .to_string(),
+        ),
+        (
+            "connected_comments",
+            Context::default()
+                .line_index(9)
+                .lines(0, "let total = price * amount + shipping;")
+                .add_highlight((0, 12..17, "f64"))
+                .add_highlight((0, 20..26, "u32"))
+                .add_highlight((0, 29..37, "Option<f64>"))
+                .to_string(),
+        ),
+        (
+            "long_line",
+            Context::default()
+                .source("data.csv")
+                .line_index(99)
+                .lines(0, format!("{}80o0{}", "1,".repeat(70), ",2".repeat(70)))
+                .add_highlight((0, 140..144, "not a number"))
+                .to_string(),
+        ),
+        (
+            "footnotes",
+            Context::default()
+                .lines(0, "a = b + c + d")
+                .add_highlight((0, 4..5, "undefined variable"))
+                .add_highlight((0, 8..9, "undefined variable"))
+                .add_highlight((0, 12..13, "undefined variable"))
+                .to_string(),
+        ),
+        (
+            "control_characters",
+            Context::default()
+                .line_index(0)
+                .lines(0, "a\tb\0c\u{7f}d\u{202E}e")
+                .add_highlight((0, 0..9))
+                .to_string(),
+        ),
+        (
+            "no_text",
+            Context::default()
+                .source("data.csv")
+                .line_index(4)
+                .add_highlight((0, 3..5))
+                .to_string(),
+        ),
+        (
+            "hexdump",
+            Context::hexdump(b"MZ\x90\x00binary data", 2..4).to_string(),
+        ),
+        (
+            "key_path",
+            Context::key_path(["servers", "0", "port"])
+                .source("config.toml")
+                .to_string(),
+        ),
+        (
+            "full_error",
+            BoxedError::new(
+                BasicKind::Error,
+                "Invalid number",
+                "This column is not a number",
+                Context::default()
+                    .source("data.csv")
+                    .line_index(1)
+                    .lines(0, "1,80o0,mg")
+                    .add_highlight((0, 2..6)),
+            )
+            .suggestions(["8000", "80"])
+            .version("v1.2")
+            .add_underlying_error(BoxedError::message(BasicKind::Warning, "Parsed as text"))
+            .to_string(),
+        ),
+    ]
+}
+
+#[test]
+fn golden() {
+    DisplayConfig::default()
+        .colour(false)
+        .symbol_set(SymbolSet::Unicode)
+        .set_thread_local();
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failed = Vec::new();
+    for (name, rendered) in cases() {
+        let path = directory.join(format!("{name}.txt"));
+        let rendered = format!("{rendered}\n");
+        if update {
+            std::fs::write(&path, &rendered).unwrap();
+        } else if std::fs::read_to_string(&path).ok().as_deref() != Some(rendered.as_str()) {
+            eprintln!(
+                "Rendering of '{name}' differs from {}:\n{rendered}",
+                path.display()
+            );
+            failed.push(name);
+        }
+    }
+    assert!(failed.is_empty(), "Changed renderings: {failed:?}");
+}